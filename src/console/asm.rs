@@ -0,0 +1,328 @@
+use std::{fmt, cmp, clone, fs, io, path};
+use std::collections::{HashMap, BTreeSet};
+use crate::console::instruction::{Instruction, Operation};
+
+/// What went wrong on an assembly line, counted from 1
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct AssembleError {
+    pub line: usize,
+    pub kind: AssembleErrorKind,
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub enum AssembleErrorKind {
+    UnknownOperation(String),
+    MissingArgument,
+    InvalidArgument(String),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    /// Labels are a letter or `_` followed by letters, digits and `_`
+    InvalidLabel(String),
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            AssembleErrorKind::UnknownOperation(operation) => write!(f, "unknown operation `{}`", operation),
+            AssembleErrorKind::MissingArgument => write!(f, "missing argument"),
+            AssembleErrorKind::InvalidArgument(argument) => write!(f, "invalid argument `{}`", argument),
+            AssembleErrorKind::UndefinedLabel(label) => write!(f, "undefined label `{}`", label),
+            AssembleErrorKind::DuplicateLabel(label) => write!(f, "label `{}` is already defined", label),
+            AssembleErrorKind::InvalidLabel(label) => write!(f, "invalid label `{}`", label),
+        }
+    }
+}
+
+enum Argument<'a> {
+    Offset(i64),
+    Label(&'a str),
+}
+
+/// Compiles assembly to instructions. Every line holds an optional `label:`, an optional
+/// instruction and an optional `;` comment. `jmp` and `nop` take a label or a relative
+/// offset, `acc` only a number. A label after the last instruction marks the end.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>, AssembleError> {
+    let mut labels: HashMap<&str, i64> = HashMap::new();
+    let mut parsed: Vec<(usize, Operation, Argument)> = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let error = |kind| AssembleError{line: i + 1, kind};
+        let mut code = line.split(';').next().unwrap_or("").trim();
+        if let Some(colon) = code.find(':') {
+            let label = code[..colon].trim();
+            if !is_label(label) {
+                return Err(error(AssembleErrorKind::InvalidLabel(label.to_string())));
+            }
+            if labels.insert(label, parsed.len() as i64).is_some() {
+                return Err(error(AssembleErrorKind::DuplicateLabel(label.to_string())));
+            }
+            code = code[colon + 1..].trim();
+        }
+        let mut words = code.split_whitespace();
+        let operation = match words.next() {
+            Some(word) => word.parse::<Operation>()
+                .or(Err(error(AssembleErrorKind::UnknownOperation(word.to_string()))))?,
+            None => continue,
+        };
+        let argument = match words.next() {
+            Some(word) => match word.parse::<i64>() {
+                Ok(offset) => Argument::Offset(offset),
+                Err(_e) if operation != Operation::ACC && is_label(word) => Argument::Label(word),
+                Err(_e) => return Err(error(AssembleErrorKind::InvalidArgument(word.to_string()))),
+            },
+            None => return Err(error(AssembleErrorKind::MissingArgument)),
+        };
+        if let Some(word) = words.next() {
+            return Err(error(AssembleErrorKind::InvalidArgument(word.to_string())));
+        }
+        parsed.push((i + 1, operation, argument));
+    }
+    parsed.into_iter()
+        .enumerate()
+        .map(|(address, (line, operation, argument))| {
+            let argument = match argument {
+                Argument::Offset(offset) => offset,
+                Argument::Label(label) => match labels.get(label) {
+                    Some(target) => target - address as i64,
+                    None => return Err(AssembleError{line, kind: AssembleErrorKind::UndefinedLabel(label.to_string())}),
+                },
+            };
+            Ok(Instruction{operation, argument})
+        })
+        .collect()
+}
+
+fn is_label(word: &str) -> bool {
+    word.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Assembly that `assemble` turns back into `program`. Jumps that land inside the program,
+/// or just past it, get a label; the rest keep their offset.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let targets: BTreeSet<i64> = program.iter()
+        .enumerate()
+        .filter(|(_address, instruction)| instruction.operation == Operation::JMP)
+        .filter_map(|(address, instruction)| (address as i64).checked_add(instruction.argument))
+        .filter(|target| *target >= 0 && *target <= program.len() as i64)
+        .collect();
+    let label = |target: i64| format!("L{}", targets.range(..target).count());
+    let mut source = String::new();
+    for (address, instruction) in program.iter().enumerate() {
+        let address = address as i64;
+        if targets.contains(&address) {
+            source.push_str(&format!("{}:\n", label(address)));
+        }
+        let target = match instruction.operation {
+            Operation::JMP => address.checked_add(instruction.argument).filter(|target| targets.contains(target)),
+            _ => None,
+        };
+        match target {
+            Some(target) => source.push_str(&format!("    {} {}\n", instruction.operation, label(target))),
+            None => source.push_str(&format!("    {}\n", instruction)),
+        }
+    }
+    if targets.contains(&(program.len() as i64)) {
+        source.push_str(&format!("{}:\n", label(program.len() as i64)));
+    }
+    source
+}
+
+const MAGIC: &[u8; 4] = b"HHC\x01";
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub enum DecodeError {
+    BadMagic,
+    Truncated,
+    UnknownOpcode(u8),
+    /// A varint longer than any `i64` needs, or with bits past the 64th
+    InvalidVarint,
+    /// Bytes left over after the last instruction
+    TrailingData(usize),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::BadMagic => write!(f, "not a console program"),
+            DecodeError::Truncated => write!(f, "unexpected end of data"),
+            DecodeError::UnknownOpcode(opcode) => write!(f, "unknown opcode {}", opcode),
+            DecodeError::InvalidVarint => write!(f, "invalid varint"),
+            DecodeError::TrailingData(count) => write!(f, "{} bytes after the last instruction", count),
+        }
+    }
+}
+
+/// `HHC\x01`, the number of instructions as a varint, then per instruction an opcode byte
+/// and the zigzag-encoded argument as a varint. Most instructions fit in two or three bytes.
+pub fn encode(program: &[Instruction]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    write_varint(&mut bytes, program.len() as u64);
+    for instruction in program {
+        bytes.push(match instruction.operation {
+            Operation::NOP => 0,
+            Operation::ACC => 1,
+            Operation::JMP => 2,
+        });
+        let argument = instruction.argument;
+        write_varint(&mut bytes, ((argument << 1) ^ (argument >> 63)) as u64);
+    }
+    bytes
+}
+
+pub fn decode(bytes: &[u8]) -> Result<Vec<Instruction>, DecodeError> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(DecodeError::BadMagic);
+    }
+    let mut rest = &bytes[MAGIC.len()..];
+    let count = read_varint(&mut rest)?;
+    let mut program: Vec<Instruction> = Vec::new();
+    for _i in 0..count {
+        let (opcode, tail) = rest.split_first().ok_or(DecodeError::Truncated)?;
+        rest = tail;
+        let operation = match opcode {
+            0 => Operation::NOP,
+            1 => Operation::ACC,
+            2 => Operation::JMP,
+            _ => return Err(DecodeError::UnknownOpcode(*opcode)),
+        };
+        let zigzag = read_varint(&mut rest)?;
+        program.push(Instruction{
+            operation,
+            argument: (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64),
+        });
+    }
+    if !rest.is_empty() {
+        return Err(DecodeError::TrailingData(rest.len()));
+    }
+    Ok(program)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, DecodeError> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let (byte, rest) = bytes.split_first().ok_or(DecodeError::Truncated)?;
+        *bytes = rest;
+        // Only the lowest bit of the tenth byte still fits in 64 bits
+        if shift == 63 && byte & 0x7e != 0 {
+            return Err(DecodeError::InvalidVarint);
+        }
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(DecodeError::InvalidVarint)
+}
+
+pub fn save<P: AsRef<path::Path>>(path: P, program: &[Instruction]) -> io::Result<()> {
+    fs::write(path, encode(program))
+}
+
+pub fn load<P: AsRef<path::Path>>(path: P) -> io::Result<Vec<Instruction>> {
+    decode(&fs::read(path)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn parse(program: &str) -> Vec<Instruction> {
+        program.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn assemble_labels() {
+        let source = "\
+; adds 3 until the loop is noticed
+start:  nop +0
+        acc +1      ; counter
+        jmp skip
+back:   acc +3
+        jmp start+1
+";
+        assert_eq!(assemble(source).unwrap_err(), AssembleError{
+            line: 6,
+            kind: AssembleErrorKind::InvalidArgument(String::from("start+1")),
+        });
+        let source = source.replace("start+1", "counted") + "skip:\n    acc -99\ncounted: jmp back\nend:\n";
+        assert_eq!(assemble(&source).unwrap(), parse("nop +0\nacc +1\njmp +3\nacc +3\njmp +2\nacc -99\njmp -3"));
+    }
+
+    #[test]
+    fn assemble_errors() {
+        let error = |source: &str| assemble(source).unwrap_err();
+        assert_eq!(error("nop +0\nmul +2"), AssembleError{line: 2, kind: AssembleErrorKind::UnknownOperation(String::from("mul"))});
+        assert_eq!(error("jmp"), AssembleError{line: 1, kind: AssembleErrorKind::MissingArgument});
+        assert_eq!(error("acc start\nstart: nop +0"), AssembleError{line: 1, kind: AssembleErrorKind::InvalidArgument(String::from("start"))});
+        assert_eq!(error("nop +0\n\njmp nowhere"), AssembleError{line: 3, kind: AssembleErrorKind::UndefinedLabel(String::from("nowhere"))});
+        assert_eq!(error("a: nop +0\na: nop +0"), AssembleError{line: 2, kind: AssembleErrorKind::DuplicateLabel(String::from("a"))});
+        assert_eq!(error("jmp +1 +2").to_string(), "line 1: invalid argument `+2`");
+        assert_eq!(error(": nop +0"), AssembleError{line: 1, kind: AssembleErrorKind::InvalidLabel(String::new())});
+        assert_eq!(error("nop +0\ntwo words: nop +0"), AssembleError{line: 2, kind: AssembleErrorKind::InvalidLabel(String::from("two words"))});
+        assert_eq!(error("-1: acc +1").to_string(), "line 1: invalid label `-1`");
+        assert_eq!(error("1st: acc +1"), AssembleError{line: 1, kind: AssembleErrorKind::InvalidLabel(String::from("1st"))});
+    }
+
+    #[test]
+    fn disassemble_round_trip() {
+        let program = parse(PROGRAM);
+        let source = disassemble(&program);
+        assert_eq!(source, "    nop +0\nL0:\n    acc +1\n    jmp L2\nL1:\n    acc +3\n    jmp L0\n    acc -99\nL2:\n    acc +1\n    jmp L1\n    acc +6\n");
+        assert_eq!(assemble(&source).unwrap(), program);
+        let escaping = parse("jmp +3\nnop +5\njmp -4");
+        assert_eq!(disassemble(&escaping), "    jmp L0\n    nop +5\n    jmp -4\nL0:\n");
+        assert_eq!(assemble(&disassemble(&escaping)).unwrap(), escaping);
+        let extremes = parse("nop +0\nacc +9223372036854775807\njmp +9223372036854775807\njmp -9223372036854775808\nacc -9223372036854775808\nnop -9223372036854775808");
+        assert_eq!(assemble(&disassemble(&extremes)).unwrap(), extremes);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let program = parse(PROGRAM);
+        let bytes = encode(&program);
+        assert_eq!(bytes[..6], [b'H', b'H', b'C', 1, 9, 0]);
+        assert_eq!(bytes.len(), 4 + 1 + 2 * 9 + 1);
+        assert_eq!(decode(&bytes).unwrap(), program);
+        let extremes = vec![
+            Instruction{operation: Operation::ACC, argument: i64::MIN},
+            Instruction{operation: Operation::JMP, argument: i64::MAX},
+        ];
+        assert_eq!(decode(&encode(&extremes)).unwrap(), extremes);
+        assert_eq!(decode(&[&MAGIC[..], &[0x01, 0x00], &[0xff; 9], &[0x01]].concat()).unwrap(), vec![
+            Instruction{operation: Operation::NOP, argument: i64::MIN},
+        ]);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let bytes = encode(&parse(PROGRAM));
+        assert_eq!(decode(b"HHC\x02\x00"), Err(DecodeError::BadMagic));
+        assert_eq!(decode(&bytes[..bytes.len() - 1]), Err(DecodeError::Truncated));
+        assert_eq!(decode(b"HHC\x01\x01\x07\x00"), Err(DecodeError::UnknownOpcode(7)));
+        assert_eq!(decode(&[&MAGIC[..], &[0xff; 11]].concat()), Err(DecodeError::InvalidVarint));
+        assert_eq!(decode(&[&MAGIC[..], &[0xff; 9], &[0x02]].concat()), Err(DecodeError::InvalidVarint));
+        assert_eq!(decode(&[&bytes[..], &[0, 0]].concat()), Err(DecodeError::TrailingData(2)));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join(format!("aoc-2020-console-{}.bin", std::process::id()));
+        let program = parse(PROGRAM);
+        save(&path, &program).unwrap();
+        assert_eq!(load(&path).unwrap(), program);
+        fs::write(&path, b"nop +0").unwrap();
+        assert_eq!(load(&path).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(&path).unwrap();
+    }
+}
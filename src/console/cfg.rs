@@ -0,0 +1,211 @@
+use std::{fmt, cmp, clone};
+use crate::console::instruction::{Instruction, Operation};
+
+/// Swapping the operation of the instruction at `address` between `jmp` and `nop`
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct Flip {
+    pub address: usize,
+    pub replacement: Instruction,
+}
+
+impl Flip {
+    pub fn apply(&self, program: &[Instruction]) -> Vec<Instruction> {
+        let mut patched = program.to_vec();
+        patched[self.address] = self.replacement.clone();
+        patched
+    }
+}
+
+fn flipped(instruction: &Instruction) -> Option<Instruction> {
+    let operation = match instruction.operation {
+        Operation::JMP => Operation::NOP,
+        Operation::NOP => Operation::JMP,
+        Operation::ACC => return None,
+    };
+    Some(Instruction{
+        operation,
+        argument: instruction.argument,
+    })
+}
+
+/// Every instruction has exactly one successor, so the graph is a set of chains. Address
+/// `len` stands for termination; jumps anywhere else outside the program have no successor.
+#[derive(fmt::Debug, clone::Clone)]
+pub struct ControlFlowGraph<'a> {
+    program: &'a [Instruction],
+    successors: Vec<Option<usize>>,
+    /// Entry and exit times of a depth-first search from the exit over reversed edges. An
+    /// address lies on the path from another to the exit iff its interval contains the other's.
+    intervals: Vec<Option<(usize, usize)>>,
+}
+
+impl<'a> ControlFlowGraph<'a> {
+    pub fn new(program: &'a [Instruction]) -> Self {
+        let successors: Vec<Option<usize>> = program.iter()
+            .enumerate()
+            .map(|(address, instruction)| successor(program, address, instruction))
+            .collect();
+        let exit = program.len();
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); exit + 1];
+        for (address, next) in successors.iter().enumerate() {
+            if let Some(next) = next {
+                predecessors[*next].push(address);
+            }
+        }
+        let mut intervals: Vec<Option<(usize, usize)>> = vec![None; exit + 1];
+        let mut time = 0;
+        let mut stack: Vec<(usize, bool)> = vec![(exit, false)];
+        while let Some((address, finished)) = stack.pop() {
+            if finished {
+                intervals[address] = intervals[address].map(|(entry, _exit)| (entry, time));
+                continue;
+            }
+            intervals[address] = Some((time, time));
+            time += 1;
+            stack.push((address, true));
+            for predecessor in &predecessors[address] {
+                stack.push((*predecessor, false));
+            }
+        }
+        ControlFlowGraph{
+            program,
+            successors,
+            intervals,
+        }
+    }
+    /// Whether running from `address` ends just past the last instruction
+    pub fn terminates_from(&self, address: usize) -> bool {
+        self.intervals[address].is_some()
+    }
+    /// Addresses in the order the unmodified program runs them, until it repeats one or leaves
+    pub fn reachable(&self) -> Vec<usize> {
+        let mut visited = vec![false; self.program.len()];
+        let mut path: Vec<usize> = Vec::new();
+        let mut address = Some(0);
+        while let Some(current) = address {
+            if current >= self.program.len() || visited[current] {
+                break;
+            }
+            visited[current] = true;
+            path.push(current);
+            address = self.successors[current];
+        }
+        path
+    }
+    /// Whether `address` is visited on the way from `from` to the exit
+    fn passes_through(&self, from: usize, address: usize) -> bool {
+        match (self.intervals[from], self.intervals[address]) {
+            (Some((from_entry, from_exit)), Some((entry, exit))) => entry <= from_entry && from_exit <= exit,
+            _ => false,
+        }
+    }
+    /// Every single `jmp`/`nop` flip after which the program terminates, in the order the
+    /// flipped instructions are reached. Flips the program never reaches come last, and only
+    /// if it terminates already. Linear in the length of the program.
+    pub fn terminating_flips(&self) -> Vec<Flip> {
+        let reachable = self.reachable();
+        let mut flips: Vec<Flip> = reachable.iter()
+            .filter_map(|address| {
+                let replacement = flipped(&self.program[*address])?;
+                let next = successor(self.program, *address, &replacement)?;
+                // Coming back through the flipped instruction would loop forever
+                if !self.terminates_from(next) || self.passes_through(next, *address) {
+                    return None;
+                }
+                Some(Flip{address: *address, replacement})
+            })
+            .collect();
+        if self.terminates_from(0) {
+            let mut unreached = vec![true; self.program.len()];
+            for address in reachable {
+                unreached[address] = false;
+            }
+            flips.extend(self.program.iter()
+                .enumerate()
+                .filter(|(address, _instruction)| unreached[*address])
+                .filter_map(|(address, instruction)| Some(Flip{address, replacement: flipped(instruction)?})));
+        }
+        flips
+    }
+    /// The first flip that makes the program terminate
+    pub fn repair(&self) -> Option<Flip> {
+        self.terminating_flips().into_iter().next()
+    }
+}
+
+fn successor(program: &[Instruction], address: usize, instruction: &Instruction) -> Option<usize> {
+    let next = match instruction.operation {
+        Operation::JMP => (address as i64).checked_add(instruction.argument)?,
+        _ => address as i64 + 1,
+    };
+    if next < 0 || next > program.len() as i64 {
+        return None;
+    }
+    Some(next as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::vm::{Vm, Halting, RunOutcome};
+
+    fn parse(program: &str) -> Vec<Instruction> {
+        program.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    fn brute_force_flips(program: &[Instruction]) -> Vec<usize> {
+        (0..program.len())
+            .filter(|address| {
+                let replacement = match flipped(&program[*address]) {
+                    Some(replacement) => replacement,
+                    None => return false,
+                };
+                let patched = Flip{address: *address, replacement}.apply(program);
+                Vm::console(&patched).run(&Halting::default()) == RunOutcome::Terminated
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repair() {
+        let program = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6");
+        let graph = ControlFlowGraph::new(&program);
+        assert_eq!(graph.reachable(), vec![0, 1, 2, 6, 7, 3, 4]);
+        assert!(graph.terminates_from(8));
+        assert!(!graph.terminates_from(0));
+        assert_eq!(graph.repair(), Some(Flip{address: 7, replacement: "nop -4".parse().unwrap()}));
+    }
+
+    #[test]
+    fn flips_that_loop_back() {
+        // Already terminates through 0 and 1. Flipping either of them sends the program
+        // to 2, whose path to the exit runs through the flipped instruction again.
+        let program = parse("nop +2\njmp +2\njmp -2");
+        let graph = ControlFlowGraph::new(&program);
+        assert!(graph.terminates_from(0));
+        assert_eq!(graph.terminating_flips(), vec![Flip{address: 2, replacement: "nop -2".parse().unwrap()}]);
+        assert_eq!(brute_force_flips(&program), vec![2]);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let programs = [
+            "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6",
+            "nop +2\njmp +0\nacc +1\nnop +1",
+            "jmp +2\nnop -1\njmp -1\nacc +1",
+            "nop +3\njmp +2\nacc +1\njmp -3\nnop -1\njmp +7",
+            "jmp +1\njmp +1\njmp +1",
+            "nop +0\njmp +9223372036854775807\nnop +9223372036854775807\nacc +1",
+            "nop -9223372036854775808\njmp -9223372036854775808\nnop +1\njmp -1",
+        ];
+        for program in &programs {
+            let program = parse(program);
+            let flips: Vec<usize> = ControlFlowGraph::new(&program).terminating_flips().iter()
+                .map(|flip| flip.address)
+                .collect();
+            let mut sorted = flips.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, brute_force_flips(&program));
+        }
+    }
+}
@@ -0,0 +1,588 @@
+use std::{str, fmt, cmp, clone, marker, io};
+use std::collections::HashSet;
+use crate::console::vm::{Vm, Op, State, Fault};
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    pub fn holds(&self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum Breakpoint {
+    /// Stops before the instruction at this address runs
+    Address(i64),
+    /// Stops as soon as the accumulator starts to satisfy the comparison, so a
+    /// condition that keeps holding does not stop every following instruction
+    Accumulator(Comparison, i64),
+}
+
+impl Breakpoint {
+    /// Whether an instruction that went from `before` to `after` stops here
+    pub fn is_hit(&self, before: &State, after: &State) -> bool {
+        match self {
+            Breakpoint::Address(pc) => after.pc == *pc,
+            Breakpoint::Accumulator(comparison, value) =>
+                !comparison.holds(before.accumulator(), *value) && comparison.holds(after.accumulator(), *value),
+        }
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum Operand {
+    Pc,
+    Accumulator,
+    /// Argument of the instruction at the program counter
+    Argument,
+    Literal(i64),
+}
+
+/// Sum of operands such as `acc + arg - 1`, evaluated after every stop
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct Watch {
+    text: String,
+    terms: Vec<(i64, Operand)>,
+}
+
+#[derive(fmt::Debug)]
+pub struct ParseCommandError;
+
+impl Watch {
+    /// `None` when the expression refers to the argument but the program has halted, or
+    /// when the sum overflows
+    pub fn evaluate(&self, state: &State, program: &[Op]) -> Option<i64> {
+        let mut total: i64 = 0;
+        for (sign, operand) in &self.terms {
+            let value = match operand {
+                Operand::Pc => state.pc,
+                Operand::Accumulator => state.accumulator(),
+                Operand::Argument => *fetch(program, state.pc)?.args.first()?,
+                Operand::Literal(value) => *value,
+            };
+            total = sign.checked_mul(value).and_then(|term| total.checked_add(term))?;
+        }
+        Some(total)
+    }
+}
+
+impl str::FromStr for Operand {
+    type Err = ParseCommandError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pc" => Ok(Operand::Pc),
+            "acc" => Ok(Operand::Accumulator),
+            "arg" => Ok(Operand::Argument),
+            _ => s.parse().map(Operand::Literal).or(Err(ParseCommandError)),
+        }
+    }
+}
+
+/// Operands and operators must be separated by spaces
+impl str::FromStr for Watch {
+    type Err = ParseCommandError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = s.split_whitespace();
+        let mut terms = vec![(1, tokens.next().ok_or(ParseCommandError)?.parse()?)];
+        while let Some(operator) = tokens.next() {
+            let sign = match operator {
+                "+" => 1,
+                "-" => -1,
+                _ => return Err(ParseCommandError),
+            };
+            terms.push((sign, tokens.next().ok_or(ParseCommandError)?.parse()?));
+        }
+        Ok(Watch{
+            text: s.split_whitespace().collect::<Vec<&str>>().join(" "),
+            terms,
+        })
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Why execution stopped
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum Stop {
+    Stepped,
+    Breakpoint(Breakpoint),
+    /// The instruction at `pc` is about to run for the second time
+    Loop { pc: i64 },
+    /// The program counter is just past the last instruction
+    Terminated,
+    /// The program counter is anywhere else outside the program
+    Crashed { pc: i64 },
+    /// The instruction at `pc` could not run
+    Fault { pc: i64, fault: Fault },
+    /// Reverse-stepping reached the initial state
+    StartOfHistory,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Stepped => write!(f, "Stepped"),
+            Stop::Breakpoint(Breakpoint::Address(pc)) => write!(f, "Breakpoint at {}", pc),
+            Stop::Breakpoint(Breakpoint::Accumulator(comparison, value)) =>
+                write!(f, "Breakpoint on acc {} {}", symbol(comparison), value),
+            Stop::Loop{pc} => write!(f, "Loop: {} would run a second time", pc),
+            Stop::Terminated => write!(f, "Terminated"),
+            Stop::Crashed{pc} => write!(f, "Crashed: jumped to {}", pc),
+            Stop::Fault{pc, fault} => write!(f, "Fault at {}: {:?}", pc, fault),
+            Stop::StartOfHistory => write!(f, "At start of history"),
+        }
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub enum Command {
+    Step(usize),
+    Continue,
+    ReverseStep(usize),
+    Break(Breakpoint),
+    Delete(usize),
+    Watch(Watch),
+    Info,
+    Trace(usize),
+    List,
+    Help,
+    Quit,
+}
+
+impl str::FromStr for Command {
+    type Err = ParseCommandError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let count = |default: usize| match words.get(1) {
+            Some(word) => word.parse().or(Err(ParseCommandError)),
+            None => Ok(default),
+        };
+        match words.first().copied().unwrap_or("") {
+            "s" | "step" => Ok(Command::Step(count(1)?)),
+            "c" | "continue" => Ok(Command::Continue),
+            "r" | "reverse-step" => Ok(Command::ReverseStep(count(1)?)),
+            "b" | "break" => match words[1..] {
+                [address] => Ok(Command::Break(Breakpoint::Address(address.parse().or(Err(ParseCommandError))?))),
+                ["acc", comparison, value] => Ok(Command::Break(Breakpoint::Accumulator(
+                    parse_comparison(comparison)?,
+                    value.parse().or(Err(ParseCommandError))?,
+                ))),
+                _ => Err(ParseCommandError),
+            },
+            "d" | "delete" => Ok(Command::Delete(words.get(1).ok_or(ParseCommandError)?.parse().or(Err(ParseCommandError))?)),
+            "w" | "watch" => Ok(Command::Watch(words[1..].join(" ").parse()?)),
+            "i" | "info" => Ok(Command::Info),
+            "t" | "trace" => Ok(Command::Trace(count(usize::MAX)?)),
+            "l" | "list" => Ok(Command::List),
+            "h" | "help" => Ok(Command::Help),
+            "q" | "quit" => Ok(Command::Quit),
+            _ => Err(ParseCommandError),
+        }
+    }
+}
+
+fn parse_comparison(s: &str) -> Result<Comparison, ParseCommandError> {
+    match s {
+        "==" => Ok(Comparison::Equal),
+        "!=" => Ok(Comparison::NotEqual),
+        "<" => Ok(Comparison::Less),
+        "<=" => Ok(Comparison::LessOrEqual),
+        ">" => Ok(Comparison::Greater),
+        ">=" => Ok(Comparison::GreaterOrEqual),
+        _ => Err(ParseCommandError),
+    }
+}
+
+fn symbol(comparison: &Comparison) -> &'static str {
+    match comparison {
+        Comparison::Equal => "==",
+        Comparison::NotEqual => "!=",
+        Comparison::Less => "<",
+        Comparison::LessOrEqual => "<=",
+        Comparison::Greater => ">",
+        Comparison::GreaterOrEqual => ">=",
+    }
+}
+
+fn fetch(program: &[Op], pc: i64) -> Option<&Op> {
+    if pc < 0 {
+        return None;
+    }
+    program.get(pc as usize)
+}
+
+const HELP: &str = "\
+step [n]           run n instructions (s)
+continue           run to a breakpoint, a loop or the end (c)
+reverse-step [n]   undo n instructions (r)
+break <address>    stop before the instruction at address (b)
+break acc <op> <n> stop when acc starts to compare to n, op is one of == != < <= > >=
+delete <n>         remove breakpoint n (d)
+watch <expr>       print e.g. `acc + arg - 1` at every stop (w)
+info               show state, breakpoints and watches (i)
+trace [n]          show the last n executed instructions (t)
+list               show the program around pc (l)
+quit               leave the debugger (q)
+";
+
+/// Steps a `Vm` while keeping every earlier state, so execution can be reversed
+#[derive(fmt::Debug, clone::Clone)]
+pub struct Debugger {
+    vm: Vm,
+    history: Vec<State>,
+    breakpoints: Vec<Breakpoint>,
+    watches: Vec<Watch>,
+}
+
+impl Debugger {
+    pub fn new(vm: Vm) -> Self {
+        Debugger{
+            vm,
+            history: Vec::new(),
+            breakpoints: Vec::new(),
+            watches: Vec::new(),
+        }
+    }
+    pub fn state(&self) -> &State {
+        self.vm.state()
+    }
+    fn program(&self) -> &[Op] {
+        self.vm.program()
+    }
+    /// Every state before the current one, oldest first
+    pub fn history(&self) -> &[State] {
+        &self.history
+    }
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+    pub fn remove_breakpoint(&mut self, index: usize) -> Option<Breakpoint> {
+        if index < self.breakpoints.len() {
+            Some(self.breakpoints.remove(index))
+        } else {
+            None
+        }
+    }
+    pub fn add_watch(&mut self, watch: Watch) {
+        self.watches.push(watch);
+    }
+    /// Every watch with its current value
+    pub fn watches(&self) -> Vec<(&Watch, Option<i64>)> {
+        self.watches.iter()
+            .map(|watch| (watch, watch.evaluate(self.state(), self.program())))
+            .collect()
+    }
+    fn halted(&self) -> Option<Stop> {
+        let pc = self.state().pc;
+        if pc == self.program().len() as i64 {
+            return Some(Stop::Terminated);
+        }
+        match fetch(self.program(), pc) {
+            Some(_op) => None,
+            None => Some(Stop::Crashed{pc}),
+        }
+    }
+    pub fn step(&mut self) -> Stop {
+        if let Some(stop) = self.halted() {
+            return stop;
+        }
+        let before = self.state().clone();
+        if let Err(fault) = self.vm.step() {
+            return Stop::Fault{pc: before.pc, fault};
+        }
+        self.history.push(before);
+        self.halted().unwrap_or(Stop::Stepped)
+    }
+    /// Runs until a breakpoint is hit, the program halts or an instruction would run twice
+    pub fn resume(&mut self) -> Stop {
+        let mut visited: HashSet<i64> = self.history.iter().map(|state| state.pc).collect();
+        loop {
+            visited.insert(self.state().pc);
+            let stop = self.step();
+            if stop != Stop::Stepped {
+                return stop;
+            }
+            let before = self.history.last().unwrap();
+            if let Some(breakpoint) = self.breakpoints.iter().find(|b| b.is_hit(before, self.vm.state())) {
+                return Stop::Breakpoint(*breakpoint);
+            }
+            if visited.contains(&self.state().pc) {
+                return Stop::Loop{pc: self.state().pc};
+            }
+        }
+    }
+    pub fn reverse_step(&mut self) -> Stop {
+        match self.history.pop() {
+            Some(state) => {
+                self.vm.restore(state);
+                Stop::Stepped
+            },
+            None => Stop::StartOfHistory,
+        }
+    }
+    /// The last `count` executed instructions with the state each one started from
+    pub fn trace(&self, count: usize) -> String {
+        self.history.iter()
+            .skip(self.history.len().saturating_sub(count))
+            .map(|state| format!("{:>5}: {:<8} acc={}\n", state.pc, self.program()[state.pc as usize].to_string(), state.accumulator()))
+            .collect()
+    }
+    fn listing(&self) -> String {
+        let current = self.state().pc;
+        let first = current.saturating_sub(3).max(0);
+        let last = current.saturating_add(4).min(self.program().len() as i64);
+        (first..last)
+            .map(|pc| {
+                let marker = if pc == current { "=>" } else { "  " };
+                format!("{} {:>5}: {}\n", marker, pc, self.program()[pc as usize])
+            })
+            .collect()
+    }
+    fn status(&self) -> String {
+        let mut status = format!("pc={} acc={}", self.state().pc, self.state().accumulator());
+        if let Some(op) = fetch(self.program(), self.state().pc) {
+            status.push_str(&format!(" next: {}", op));
+        }
+        status.push('\n');
+        for (watch, value) in self.watches() {
+            match value {
+                Some(value) => status.push_str(&format!("  {} = {}\n", watch, value)),
+                None => status.push_str(&format!("  {} = ?\n", watch)),
+            }
+        }
+        status
+    }
+    /// Runs one command and returns what to print
+    pub fn execute(&mut self, command: Command) -> String {
+        match command {
+            Command::Step(count) | Command::ReverseStep(count) => {
+                let mut stop = Stop::Stepped;
+                for _i in 0..count {
+                    stop = match command {
+                        Command::Step(_) => self.step(),
+                        _ => self.reverse_step(),
+                    };
+                    if stop != Stop::Stepped {
+                        break;
+                    }
+                }
+                format!("{}\n{}", stop, self.status())
+            },
+            Command::Continue => {
+                let stop = self.resume();
+                format!("{}\n{}", stop, self.status())
+            },
+            Command::Break(breakpoint) => {
+                self.add_breakpoint(breakpoint);
+                format!("Breakpoint {} set\n", self.breakpoints.len() - 1)
+            },
+            Command::Delete(index) => match self.remove_breakpoint(index) {
+                Some(_breakpoint) => format!("Breakpoint {} deleted\n", index),
+                None => format!("No breakpoint {}\n", index),
+            },
+            Command::Watch(watch) => {
+                self.add_watch(watch);
+                self.status()
+            },
+            Command::Info => {
+                let mut info = self.status();
+                for (i, breakpoint) in self.breakpoints.iter().enumerate() {
+                    info.push_str(&format!("  breakpoint {}: {:?}\n", i, breakpoint));
+                }
+                info
+            },
+            Command::Trace(count) => self.trace(count),
+            Command::List => self.listing(),
+            Command::Help => HELP.to_string(),
+            Command::Quit => String::new(),
+        }
+    }
+    /// Line-oriented prompt reading commands from `input` until `quit` or end of input
+    pub fn repl<R: io::BufRead, W: io::Write>(&mut self, input: R, mut output: W) -> io::Result<()> {
+        write!(output, "(debug) ")?;
+        output.flush()?;
+        for line in input.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                match line.parse::<Command>() {
+                    Ok(Command::Quit) => return Ok(()),
+                    Ok(command) => write!(output, "{}", self.execute(command))?,
+                    Err(_e) => writeln!(output, "Unknown command: {} (try `help`)", line.trim())?,
+                }
+            }
+            write!(output, "(debug) ")?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::instruction::Instruction;
+
+    const PROGRAM: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn debugger(program: &str) -> Debugger {
+        let instructions: Vec<Instruction> = program.lines().map(|line| line.parse().unwrap()).collect();
+        Debugger::new(Vm::console(&instructions))
+    }
+
+    fn state(pc: i64, accumulator: i64) -> State {
+        State{pc, registers: vec![accumulator]}
+    }
+
+    #[test]
+    fn step_and_reverse() {
+        let mut debugger = debugger(PROGRAM);
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.state(), &state(2, 1));
+        assert_eq!(debugger.reverse_step(), Stop::Stepped);
+        assert_eq!(debugger.state(), &state(1, 0));
+        assert_eq!(debugger.reverse_step(), Stop::Stepped);
+        assert_eq!(debugger.reverse_step(), Stop::StartOfHistory);
+    }
+
+    #[test]
+    fn resume_to_loop() {
+        let mut debugger = debugger(PROGRAM);
+        assert_eq!(debugger.resume(), Stop::Loop{pc: 1});
+        assert_eq!(debugger.state().accumulator(), 5);
+    }
+
+    #[test]
+    fn resume_to_end() {
+        let mut debugger = debugger(&PROGRAM.replace("jmp -4", "nop -4"));
+        assert_eq!(debugger.resume(), Stop::Terminated);
+        assert_eq!(debugger.state().accumulator(), 8);
+        assert_eq!(debugger.step(), Stop::Terminated);
+    }
+
+    #[test]
+    fn crash() {
+        let mut debugger = debugger("jmp -2");
+        assert_eq!(debugger.step(), Stop::Crashed{pc: -2});
+    }
+
+    #[test]
+    fn list_far_outside_the_program() {
+        let mut forwards = debugger("nop +0\njmp +9223372036854775806");
+        forwards.step();
+        assert_eq!(forwards.step(), Stop::Crashed{pc: i64::MAX});
+        assert_eq!(forwards.execute(Command::List), "");
+        let mut backwards = debugger("jmp -9223372036854775808");
+        assert_eq!(backwards.step(), Stop::Crashed{pc: i64::MIN});
+        assert_eq!(backwards.execute(Command::List), "");
+    }
+
+    #[test]
+    fn fault() {
+        let mut debugger = debugger("acc +9223372036854775807\nacc +1");
+        assert_eq!(debugger.step(), Stop::Stepped);
+        assert_eq!(debugger.resume(), Stop::Fault{pc: 1, fault: Fault::Overflow});
+        assert_eq!(debugger.history().len(), 1);
+        assert_eq!(debugger.state(), &state(1, i64::MAX));
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut debugger = debugger(PROGRAM);
+        debugger.add_breakpoint(Breakpoint::Address(4));
+        debugger.add_breakpoint(Breakpoint::Accumulator(Comparison::Greater, 1));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Accumulator(Comparison::Greater, 1)));
+        assert_eq!(debugger.state(), &state(7, 2));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Address(4)));
+        assert_eq!(debugger.state(), &state(4, 5));
+        assert_eq!(debugger.remove_breakpoint(0), Some(Breakpoint::Address(4)));
+        assert_eq!(debugger.remove_breakpoint(5), None);
+    }
+
+    #[test]
+    fn accumulator_breakpoint_fires_once_per_crossing() {
+        let mut debugger = debugger(PROGRAM);
+        debugger.add_breakpoint(Breakpoint::Accumulator(Comparison::Greater, 0));
+        assert_eq!(debugger.resume(), Stop::Breakpoint(Breakpoint::Accumulator(Comparison::Greater, 0)));
+        assert_eq!(debugger.state(), &state(2, 1));
+        // Still above 0 from here on, so only the loop stops it
+        assert_eq!(debugger.resume(), Stop::Loop{pc: 1});
+        assert_eq!(debugger.state().accumulator(), 5);
+    }
+
+    #[test]
+    fn watches() {
+        let mut debugger = debugger(PROGRAM);
+        debugger.add_watch("acc + arg - 1".parse().unwrap());
+        debugger.add_watch("pc".parse().unwrap());
+        debugger.step();
+        assert_eq!(debugger.watches()[0].1, Some(0));
+        assert_eq!(debugger.watches()[1].1, Some(1));
+        assert_eq!(debugger.watches()[0].0.to_string(), "acc + arg - 1");
+        assert!("acc +".parse::<Watch>().is_err());
+        assert!("acc * 2".parse::<Watch>().is_err());
+        let program: Vec<Op> = vec!["nop +0".parse().unwrap()];
+        assert_eq!("arg".parse::<Watch>().unwrap().evaluate(&state(1, 0), &program), None);
+        assert_eq!("acc + 1".parse::<Watch>().unwrap().evaluate(&state(0, i64::MAX), &program), None);
+        assert_eq!("0 - -9223372036854775808".parse::<Watch>().unwrap().evaluate(&state(0, 0), &program), None);
+        assert_eq!("acc - 1".parse::<Watch>().unwrap().evaluate(&state(0, i64::MIN + 1), &program), Some(i64::MIN));
+    }
+
+    #[test]
+    fn trace() {
+        let mut debugger = debugger(PROGRAM);
+        debugger.execute(Command::Step(3));
+        assert_eq!(debugger.trace(2), "    1: acc +1   acc=0\n    2: jmp +4   acc=1\n");
+        assert_eq!(debugger.trace(10).lines().count(), 3);
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!("s".parse::<Command>().unwrap(), Command::Step(1));
+        assert_eq!("step 5".parse::<Command>().unwrap(), Command::Step(5));
+        assert_eq!("r 2".parse::<Command>().unwrap(), Command::ReverseStep(2));
+        assert_eq!("break 7".parse::<Command>().unwrap(), Command::Break(Breakpoint::Address(7)));
+        assert_eq!("b acc >= -3".parse::<Command>().unwrap(), Command::Break(Breakpoint::Accumulator(Comparison::GreaterOrEqual, -3)));
+        assert_eq!("t".parse::<Command>().unwrap(), Command::Trace(usize::MAX));
+        assert!("b acc ~ 3".parse::<Command>().is_err());
+        assert!("jump".parse::<Command>().is_err());
+        assert!("step x".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn repl() {
+        let mut debugger = debugger(PROGRAM);
+        let script = "break 4\nwatch acc + 1\ncontinue\nfoo\nreverse-step\nquit\nstep\n";
+        let mut output: Vec<u8> = Vec::new();
+        debugger.repl(script.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Breakpoint 0 set\n"));
+        assert!(output.contains("Breakpoint at 4\npc=4 acc=5 next: jmp -3\n  acc + 1 = 6\n"));
+        assert!(output.contains("Unknown command: foo"));
+        assert!(output.contains("Stepped\npc=3 acc=2 next: acc +3\n"));
+        assert_eq!(debugger.state(), &state(3, 2));
+    }
+}
@@ -0,0 +1,87 @@
+use std::{str, fmt, cmp, clone};
+
+#[derive(fmt::Debug)]
+pub struct ParseInstructionError;
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub enum Operation {
+    JMP,
+    ACC,
+    NOP
+}
+impl str::FromStr for Operation {
+    type Err = ParseInstructionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jmp" => Ok(Operation::JMP),
+            "acc" => Ok(Operation::ACC),
+            "nop" => Ok(Operation::NOP),
+            _ => Err(ParseInstructionError)
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::JMP => write!(f, "jmp"),
+            Operation::ACC => write!(f, "acc"),
+            Operation::NOP => write!(f, "nop"),
+        }
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct Instruction {
+    pub operation: Operation,
+    pub argument: i64,
+}
+
+impl str::FromStr for Instruction {
+    type Err = ParseInstructionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(" ").collect();
+        Ok(Instruction{
+            operation: parts[0].parse()?,
+            argument: parts.get(1).ok_or(ParseInstructionError)?.parse()
+                .or(Err(ParseInstructionError))?
+        })
+    }
+}
+
+/// Same format as the puzzle input, e.g. `jmp -3` and `acc +1`
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {:+}", self.operation, self.argument)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single_instruction() {
+        assert_eq!("nop +0".parse::<Instruction>().unwrap(), Instruction{
+            operation: Operation::NOP,
+            argument: 0
+        });
+        assert_eq!("jmp -3".parse::<Instruction>().unwrap(), Instruction{
+            operation: Operation::JMP,
+            argument: -3 
+        });
+        assert_eq!("acc +1".parse::<Instruction>().unwrap(), Instruction{
+            operation: Operation::ACC,
+            argument: 1 
+        });
+        assert!("acc".parse::<Instruction>().is_err());
+        assert!("mul +1".parse::<Instruction>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        for line in &["nop +0", "jmp -3", "acc +12"] {
+            assert_eq!(line.parse::<Instruction>().unwrap().to_string(), *line);
+        }
+    }
+}
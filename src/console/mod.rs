@@ -0,0 +1,7 @@
+pub mod instruction;
+pub mod debugger;
+pub mod vm;
+pub mod cfg;
+pub mod asm;
+pub mod optimise;
+pub mod profile;
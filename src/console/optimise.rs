@@ -0,0 +1,256 @@
+use crate::console::instruction::{Instruction, Operation};
+use crate::console::cfg::ControlFlowGraph;
+
+/// Addresses the program can never run, in ascending order
+pub fn unreachable(program: &[Instruction]) -> Vec<usize> {
+    let mut reached = vec![false; program.len()];
+    for address in ControlFlowGraph::new(program).reachable() {
+        reached[address] = true;
+    }
+    (0..program.len())
+        .filter(|address| !reached[*address])
+        .collect()
+}
+
+/// Shrinks the program without changing whether it terminates, loops or crashes, nor the
+/// accumulator at that point. Drops unreachable code, `nop`s, `acc +0` and `jmp +1`, points
+/// jumps past chains of jumps, and merges runs of `acc` no jump lands in the middle of.
+/// A jump whose target overflows becomes a plain crash instead of a fault. Programs whose
+/// accumulator overflows may fault elsewhere, since merged `acc`s skip the partial sums.
+/// `nop` arguments are not kept, so the result is no use for repairing the program.
+pub fn optimise(program: &[Instruction]) -> Vec<Instruction> {
+    let mut optimised = program.to_vec();
+    loop {
+        let next = pass(&optimised);
+        if next == optimised {
+            return next;
+        }
+        optimised = next;
+    }
+}
+
+/// Where control goes after the instruction. A jump past the range of `i64` ends up at the
+/// far end of it, outside the program either way.
+fn target(address: usize, instruction: &Instruction) -> i64 {
+    match instruction.operation {
+        Operation::JMP => (address as i64).checked_add(instruction.argument)
+            .unwrap_or(if instruction.argument > 0 { i64::MAX } else { i64::MIN }),
+        _ => address as i64 + 1,
+    }
+}
+
+fn in_program(program: &[Instruction], address: i64) -> Option<usize> {
+    if address < 0 || address >= program.len() as i64 {
+        return None;
+    }
+    Some(address as usize)
+}
+
+/// Where control ends up after following `nop`s and jumps from `start`. Stops inside a
+/// cycle of them, since that loops forever without touching the accumulator.
+fn thread(program: &[Instruction], from: usize, start: i64) -> i64 {
+    let mut visited = vec![false; program.len()];
+    visited[from] = true;
+    let mut current = start;
+    while let Some(address) = in_program(program, current) {
+        if visited[address] || program[address].operation == Operation::ACC {
+            break;
+        }
+        visited[address] = true;
+        current = target(address, &program[address]);
+    }
+    current
+}
+
+fn pass(program: &[Instruction]) -> Vec<Instruction> {
+    let length = program.len();
+    let mut keep = vec![false; length];
+    for address in ControlFlowGraph::new(program).reachable() {
+        keep[address] = true;
+    }
+    let mut targets: Vec<i64> = vec![0; length];
+    let mut is_target = vec![false; length];
+    for address in 0..length {
+        targets[address] = match program[address].operation {
+            Operation::JMP if keep[address] => thread(program, address, target(address, &program[address])),
+            _ => target(address, &program[address]),
+        };
+        if program[address].operation == Operation::JMP && keep[address] {
+            if let Some(landing) = in_program(program, targets[address]) {
+                is_target[landing] = true;
+            }
+        }
+    }
+    let mut arguments: Vec<i64> = program.iter().map(|instruction| instruction.argument).collect();
+    for address in 0..length {
+        if !keep[address] {
+            continue;
+        }
+        keep[address] = match program[address].operation {
+            Operation::NOP => false,
+            Operation::ACC => arguments[address] != 0,
+            Operation::JMP => targets[address] != address as i64 + 1,
+        };
+        if program[address].operation != Operation::ACC || !keep[address] {
+            continue;
+        }
+        let mut next = address + 1;
+        while next < length && keep[next] && !is_target[next] && program[next].operation == Operation::ACC {
+            match arguments[address].checked_add(arguments[next]) {
+                Some(sum) => arguments[address] = sum,
+                None => break,
+            }
+            keep[next] = false;
+            next += 1;
+        }
+    }
+    relocate(program, &keep, &targets, &arguments)
+}
+
+/// Drops every instruction not in `keep`. A jump into a dropped instruction lands on the
+/// next kept one, which is where falling through would have taken it.
+fn relocate(program: &[Instruction], keep: &[bool], targets: &[i64], arguments: &[i64]) -> Vec<Instruction> {
+    let mut relocated: Vec<usize> = vec![0; program.len() + 1];
+    let mut next = keep.iter().filter(|kept| **kept).count();
+    let length = next;
+    relocated[program.len()] = length;
+    for address in (0..program.len()).rev() {
+        if keep[address] {
+            next -= 1;
+        }
+        relocated[address] = next;
+    }
+    (0..program.len())
+        .filter(|address| keep[*address])
+        .map(|address| {
+            let argument = match program[address].operation {
+                Operation::JMP => {
+                    let target = targets[address];
+                    let landing = if target < 0 {
+                        -1
+                    } else if target > program.len() as i64 {
+                        length as i64 + 1
+                    } else {
+                        relocated[target as usize] as i64
+                    };
+                    landing - relocated[address] as i64
+                },
+                _ => arguments[address],
+            };
+            Instruction{
+                operation: program[address].operation.clone(),
+                argument,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem;
+    use crate::console::vm::{Vm, Halting, RunOutcome};
+
+    fn parse(program: &str) -> Vec<Instruction> {
+        program.lines().map(|line| line.parse().unwrap()).collect()
+    }
+
+    /// How the program stopped and the accumulator then. A jump that overflows counts as a
+    /// crash, and `None` stands for the accumulator overflowing.
+    fn observe(program: &[Instruction]) -> Option<(mem::Discriminant<RunOutcome>, i64)> {
+        let mut vm = Vm::console(program);
+        let outcome = match vm.run(&Halting::default()) {
+            RunOutcome::Fault{pc, ..} if program[pc as usize].operation == Operation::ACC => return None,
+            RunOutcome::Fault{pc, ..} => RunOutcome::Crashed{pc},
+            outcome => outcome,
+        };
+        Some((mem::discriminant(&outcome), vm.state().accumulator()))
+    }
+
+    /// Linear congruential generator, enough to make varied programs reproducibly
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn below(&mut self, bound: u64) -> u64 {
+            self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (self.0 >> 33) % bound
+        }
+        fn program(&mut self) -> Vec<Instruction> {
+            let length = 1 + self.below(24);
+            (0..length)
+                .map(|_i| {
+                    let (operation, range) = match self.below(3) {
+                        0 => (Operation::NOP, 6),
+                        1 => (Operation::ACC, 3),
+                        _ => (Operation::JMP, 6),
+                    };
+                    let argument = match self.below(10) {
+                        0 => [i64::MIN, i64::MIN + 1, i64::MAX - 1, i64::MAX][self.below(4) as usize],
+                        _ => self.below(2 * range + 1) as i64 - range as i64,
+                    };
+                    Instruction{operation, argument}
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let program = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6");
+        assert_eq!(unreachable(&program), vec![5, 8]);
+    }
+
+    #[test]
+    fn removes_dead_code_and_nops() {
+        let program = parse("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6");
+        assert_eq!(optimise(&program), parse("acc +1\njmp +3\nacc +3\njmp -3\nacc +1\njmp -3"));
+    }
+
+    #[test]
+    fn threads_jumps() {
+        let program = parse("jmp +2\nacc +5\njmp +2\nacc +1\nnop +0\njmp +3\nacc +2\njmp -7\nacc +4");
+        assert_eq!(optimise(&program), parse("acc +4"));
+        let cycle = parse("nop +0\njmp -1\nacc +1");
+        assert_eq!(optimise(&cycle), parse("jmp +0"));
+    }
+
+    #[test]
+    fn folds_accumulator_runs() {
+        let program = parse("acc +1\nnop +0\nacc +2\nacc -3\nacc +4\njmp +2\nacc +10\nacc +5");
+        assert_eq!(optimise(&program), parse("acc +9"));
+        let loop_into_run = parse("acc +1\nacc +2\nacc +3\njmp -2");
+        assert_eq!(optimise(&loop_into_run), parse("acc +1\nacc +5\njmp -1"));
+    }
+
+    #[test]
+    fn keeps_crashes() {
+        let program = parse("nop +0\nacc +1\njmp -5");
+        assert_eq!(optimise(&program), parse("acc +1\njmp -2"));
+        assert_eq!(optimise(&parse("acc +1\nnop +0\njmp +7")), parse("acc +1\njmp +2"));
+        assert_eq!(optimise(&parse("acc +1\njmp +9223372036854775807")), parse("acc +1\njmp +2"));
+        assert_eq!(optimise(&parse("acc +1\nnop +0\njmp -9223372036854775808")), parse("acc +1\njmp -2"));
+    }
+
+    #[test]
+    fn differential() {
+        let mut random = Lcg(2020);
+        for _i in 0..5000 {
+            let program = random.program();
+            let optimised = optimise(&program);
+            assert!(optimised.len() <= program.len());
+            if let Some(observed) = observe(&program) {
+                assert_eq!(observe(&optimised), Some(observed), "{:?} became {:?}", program, optimised);
+            }
+            assert_eq!(optimise(&optimised), optimised);
+        }
+    }
+
+    #[test]
+    fn differential_on_puzzle_input() {
+        let input = std::fs::read_to_string("src/solutions/day8.input").unwrap();
+        let program = parse(&input);
+        let optimised = optimise(&program);
+        assert!(optimised.len() < program.len());
+        assert_eq!(observe(&optimised), observe(&program));
+    }
+}
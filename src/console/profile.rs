@@ -0,0 +1,167 @@
+use std::{fmt, cmp, clone, marker};
+use crate::console::vm::{Observer, Op, State, ACCUMULATOR};
+
+/// Straight-line run of instructions that control only enters at `start`
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub struct BasicBlock {
+    pub start: usize,
+    /// Inclusive
+    pub end: usize,
+    /// Times control entered the block
+    pub hits: u64,
+}
+
+impl BasicBlock {
+    /// Instructions run inside the block altogether
+    pub fn executed(&self) -> u64 {
+        self.hits * (self.end - self.start + 1) as u64
+    }
+}
+
+/// Collects statistics while observing a `Vm` running the program it was made for. Jumps
+/// count as taken whenever control did not continue with the next address, so this works
+/// for any instruction table.
+#[derive(fmt::Debug, clone::Clone)]
+pub struct Profile {
+    program: Vec<Op>,
+    hits: Vec<u64>,
+    taken: Vec<u64>,
+    accumulator: Vec<Option<(i64, i64)>>,
+    /// Addresses control was transferred to, which start basic blocks
+    landed: Vec<bool>,
+}
+
+impl Profile {
+    pub fn for_program(program: &[Op]) -> Self {
+        let length = program.len();
+        Profile{
+            program: program.to_vec(),
+            hits: vec![0; length],
+            taken: vec![0; length],
+            accumulator: vec![None; length],
+            landed: vec![false; length],
+        }
+    }
+    pub fn hits(&self, address: usize) -> u64 {
+        self.hits[address]
+    }
+    /// Times the instruction jumped and times it fell through
+    pub fn jumps(&self, address: usize) -> (u64, u64) {
+        (self.taken[address], self.hits[address] - self.taken[address])
+    }
+    /// Smallest and largest accumulator right after the instruction ran
+    pub fn accumulator_range(&self, address: usize) -> Option<(i64, i64)> {
+        self.accumulator[address]
+    }
+    pub fn total(&self) -> u64 {
+        self.hits.iter().sum()
+    }
+    /// Basic blocks of the executed code as seen at run time, hottest first
+    pub fn hot_blocks(&self) -> Vec<BasicBlock> {
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        let mut current: Option<BasicBlock> = None;
+        for address in 0..self.hits.len() {
+            let starts_block = address == 0 || self.landed[address] || self.taken[address - 1] > 0
+                || self.hits[address] != self.hits[address - 1];
+            if self.hits[address] == 0 || starts_block {
+                blocks.extend(current.take());
+            }
+            if self.hits[address] == 0 {
+                continue;
+            }
+            match current.as_mut() {
+                Some(block) => block.end = address,
+                None => current = Some(BasicBlock{start: address, end: address, hits: self.hits[address]}),
+            }
+        }
+        blocks.extend(current);
+        blocks.sort_by(|a, b| b.executed().cmp(&a.executed()).then(a.start.cmp(&b.start)));
+        blocks
+    }
+    /// The program with hits, accumulator range and jump statistics next to every instruction
+    pub fn annotate(&self) -> String {
+        let mut listing = format!("{:>5} {:>10}  {:<24} {:<20} {}\n", "addr", "hits", "acc range", "jumps", "instruction");
+        for (address, op) in self.program.iter().enumerate() {
+            let range = match self.accumulator[address] {
+                Some((min, max)) => format!("[{}, {}]", min, max),
+                None => String::from("-"),
+            };
+            let jumps = match self.jumps(address) {
+                (0, _not_taken) => String::new(),
+                (taken, not_taken) => format!("taken {} / not {}", taken, not_taken),
+            };
+            listing.push_str(&format!("{:>5} {:>10}  {:<24} {:<20} {}\n", address, self.hits[address], range, jumps, op));
+        }
+        listing
+    }
+}
+
+impl Observer for Profile {
+    fn executed(&mut self, address: usize, state: &State) {
+        self.hits[address] += 1;
+        if state.pc != address as i64 + 1 {
+            self.taken[address] += 1;
+            if state.pc >= 0 && (state.pc as usize) < self.landed.len() {
+                self.landed[state.pc as usize] = true;
+            }
+        }
+        let accumulator = state.registers[ACCUMULATOR];
+        self.accumulator[address] = Some(match self.accumulator[address] {
+            Some((min, max)) => (min.min(accumulator), max.max(accumulator)),
+            None => (accumulator, accumulator),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::vm::{Vm, Halting, InstructionTable};
+
+    fn profile(program: &str, halting: &Halting) -> (Vm, Profile) {
+        let ops: Vec<Op> = program.lines().map(|line| line.parse().unwrap()).collect();
+        let mut vm = Vm::new(ops, &InstructionTable::standard(), 1).unwrap();
+        let mut profile = Profile::for_program(vm.program());
+        vm.run_observed(halting, &mut profile);
+        (vm, profile)
+    }
+
+    #[test]
+    fn counts() {
+        let (vm, profile) = profile("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6", &Halting::default());
+        assert_eq!((0..9).map(|address| profile.hits(address)).collect::<Vec<u64>>(), vec![1, 1, 1, 1, 1, 0, 1, 1, 0]);
+        assert_eq!(profile.total(), vm.steps() as u64);
+        assert_eq!(profile.jumps(2), (1, 0));
+        assert_eq!(profile.jumps(1), (0, 1));
+        assert_eq!(profile.accumulator_range(3), Some((5, 5)));
+        assert_eq!(profile.accumulator_range(5), None);
+    }
+
+    #[test]
+    fn hot_blocks() {
+        // Decrements forever, until the step limit stops it after six rounds of the loop
+        let halting = Halting{detect_loops: false, step_limit: Some(13), strict_bounds: true};
+        let (vm, profile) = profile("acc +3\nacc -1\njmp -1\nacc +7", &halting);
+        assert_eq!(profile.total(), vm.steps() as u64);
+        assert_eq!(profile.hits(1), 6);
+        assert_eq!(profile.accumulator_range(1), Some((-3, 2)));
+        assert_eq!(profile.hot_blocks(), vec![
+            BasicBlock{start: 1, end: 2, hits: 6},
+            BasicBlock{start: 0, end: 0, hits: 1},
+        ]);
+        assert_eq!(profile.hot_blocks()[0].executed(), 12);
+    }
+
+    #[test]
+    fn annotate() {
+        let (_vm, profile) = profile("acc +2\njmp +2\nacc +9\nacc -1", &Halting::default());
+        let listing = profile.annotate();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].trim_start().starts_with("addr"));
+        assert!(lines[2].contains("taken 1 / not 0"));
+        assert!(lines[2].ends_with("jmp +2"));
+        assert!(lines[3].contains(" 0  -"));
+        assert!(lines[4].contains("[1, 1]"));
+    }
+}
@@ -0,0 +1,380 @@
+use std::{str, fmt, cmp, clone, marker, convert, default};
+use std::collections::{HashMap, HashSet};
+use crate::console::instruction::Instruction;
+
+/// Register the standard instructions use as the accumulator
+pub const ACCUMULATOR: usize = 0;
+
+/// Registers and program counter. There is always at least the accumulator register.
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct State {
+    pub pc: i64,
+    pub registers: Vec<i64>,
+}
+
+impl State {
+    pub fn new(registers: usize) -> Self {
+        State{
+            pc: 0,
+            registers: vec![0; registers.max(1)],
+        }
+    }
+    pub fn accumulator(&self) -> i64 {
+        self.registers[ACCUMULATOR]
+    }
+    /// The register an instruction argument names
+    pub fn register(&self, index: i64) -> Result<i64, Fault> {
+        if index < 0 {
+            return Err(Fault::InvalidRegister(index));
+        }
+        self.registers.get(index as usize).copied().ok_or(Fault::InvalidRegister(index))
+    }
+    pub fn register_mut(&mut self, index: i64) -> Result<&mut i64, Fault> {
+        if index < 0 {
+            return Err(Fault::InvalidRegister(index));
+        }
+        self.registers.get_mut(index as usize).ok_or(Fault::InvalidRegister(index))
+    }
+}
+
+/// Why an instruction could not run
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum Fault {
+    /// The argument names a register the VM does not have
+    InvalidRegister(i64),
+    Overflow,
+}
+
+/// An instruction as the VM sees it: a mnemonic and its integer arguments
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct Op {
+    pub mnemonic: String,
+    pub args: Vec<i64>,
+}
+
+#[derive(fmt::Debug)]
+pub struct ParseOpError;
+
+impl str::FromStr for Op {
+    type Err = ParseOpError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        Ok(Op{
+            mnemonic: words.next().ok_or(ParseOpError)?.to_string(),
+            args: words
+                .map(|word| word.parse().or(Err(ParseOpError)))
+                .collect::<Result<Vec<i64>, ParseOpError>>()?,
+        })
+    }
+}
+
+impl convert::From<&Instruction> for Op {
+    fn from(instruction: &Instruction) -> Self {
+        Op{
+            mnemonic: instruction.operation.to_string(),
+            args: vec![instruction.argument],
+        }
+    }
+}
+
+/// Formats like an `Instruction`, e.g. `jnz +1 -2`
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mnemonic)?;
+        for arg in &self.args {
+            write!(f, " {:+}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// Carries out one instruction. Handlers move the program counter themselves, and leave
+/// the state untouched when they fault.
+pub type Handler = fn(&mut State, &[i64]) -> Result<(), Fault>;
+
+#[derive(fmt::Debug, clone::Clone, default::Default)]
+pub struct InstructionTable {
+    handlers: HashMap<String, (usize, Handler)>,
+}
+
+impl InstructionTable {
+    pub fn new() -> Self {
+        InstructionTable{
+            handlers: HashMap::new(),
+        }
+    }
+    /// `acc`, `jmp` and `nop`, as in the puzzle
+    pub fn standard() -> Self {
+        let mut table = InstructionTable::new();
+        table.register("acc", 1, |state, args| {
+            state.registers[ACCUMULATOR] = state.accumulator().checked_add(args[0]).ok_or(Fault::Overflow)?;
+            state.pc += 1;
+            Ok(())
+        });
+        table.register("jmp", 1, |state, args| {
+            state.pc = state.pc.checked_add(args[0]).ok_or(Fault::Overflow)?;
+            Ok(())
+        });
+        table.register("nop", 1, |state, _args| {
+            state.pc += 1;
+            Ok(())
+        });
+        table
+    }
+    /// Adds or replaces an instruction taking exactly `arity` arguments
+    pub fn register(&mut self, mnemonic: &str, arity: usize, handler: Handler) {
+        self.handlers.insert(mnemonic.to_string(), (arity, handler));
+    }
+    pub fn get(&self, mnemonic: &str) -> Option<(usize, Handler)> {
+        self.handlers.get(mnemonic).copied()
+    }
+}
+
+/// An op whose mnemonic is missing from the table or whose argument count is wrong
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq)]
+pub struct LoadError {
+    pub address: usize,
+    pub mnemonic: String,
+}
+
+/// When a run stops besides running off the end of the program
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub struct Halting {
+    /// Stop before an address runs for the second time
+    pub detect_loops: bool,
+    pub step_limit: Option<usize>,
+    /// Leaving the program anywhere but just past its end counts as a crash
+    pub strict_bounds: bool,
+}
+
+impl default::Default for Halting {
+    fn default() -> Self {
+        Halting{
+            detect_loops: true,
+            step_limit: None,
+            strict_bounds: true,
+        }
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum RunOutcome {
+    Terminated,
+    /// The instruction at `pc` was about to run a second time
+    Loop { pc: i64 },
+    /// The program counter left the program at `pc`
+    Crashed { pc: i64 },
+    /// The instruction at `pc` could not run
+    Fault { pc: i64, fault: Fault },
+    StepLimit,
+}
+
+/// Told about every instruction the VM runs, e.g. to profile a program
+pub trait Observer {
+    /// The instruction at `address` has just run and left the VM in `state`
+    fn executed(&mut self, address: usize, state: &State);
+}
+
+impl Observer for () {
+    fn executed(&mut self, _address: usize, _state: &State) {}
+}
+
+#[derive(fmt::Debug, clone::Clone)]
+pub struct Vm {
+    program: Vec<Op>,
+    code: Vec<Handler>,
+    state: State,
+    steps: usize,
+}
+
+impl Vm {
+    pub fn new(program: Vec<Op>, table: &InstructionTable, registers: usize) -> Result<Self, LoadError> {
+        let mut code: Vec<Handler> = Vec::new();
+        for (address, op) in program.iter().enumerate() {
+            match table.get(&op.mnemonic) {
+                Some((arity, handler)) if arity == op.args.len() => code.push(handler),
+                _ => return Err(LoadError{address, mnemonic: op.mnemonic.clone()}),
+            }
+        }
+        Ok(Vm{
+            program,
+            code,
+            state: State::new(registers),
+            steps: 0,
+        })
+    }
+    /// The puzzle's machine: the standard instructions and a single accumulator
+    pub fn console(instructions: &[Instruction]) -> Self {
+        Vm::new(instructions.iter().map(Op::from).collect(), &InstructionTable::standard(), 1).unwrap()
+    }
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+    pub fn program(&self) -> &[Op] {
+        &self.program
+    }
+    /// Number of instructions run so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    /// Goes back to an earlier state, e.g. to undo steps. The step count is kept.
+    pub fn restore(&mut self, state: State) {
+        self.state = state;
+    }
+    fn address(&self) -> Option<usize> {
+        if self.state.pc < 0 || self.state.pc as usize >= self.program.len() {
+            return None;
+        }
+        Some(self.state.pc as usize)
+    }
+    /// Runs the instruction at the program counter. Returns false, doing nothing, once the
+    /// counter is outside the program. A fault leaves the VM as it was.
+    pub fn step(&mut self) -> Result<bool, Fault> {
+        self.step_observed(&mut ())
+    }
+    pub fn step_observed<O: Observer>(&mut self, observer: &mut O) -> Result<bool, Fault> {
+        match self.address() {
+            Some(address) => {
+                (self.code[address])(&mut self.state, &self.program[address].args)?;
+                self.steps += 1;
+                observer.executed(address, &self.state);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+    pub fn run(&mut self, halting: &Halting) -> RunOutcome {
+        self.run_observed(halting, &mut ())
+    }
+    pub fn run_observed<O: Observer>(&mut self, halting: &Halting, observer: &mut O) -> RunOutcome {
+        let mut visited: HashSet<i64> = HashSet::new();
+        loop {
+            if self.address().is_none() {
+                if !halting.strict_bounds || self.state.pc == self.program.len() as i64 {
+                    return RunOutcome::Terminated;
+                }
+                return RunOutcome::Crashed{pc: self.state.pc};
+            }
+            if halting.detect_loops && !visited.insert(self.state.pc) {
+                return RunOutcome::Loop{pc: self.state.pc};
+            }
+            if halting.step_limit.is_some_and(|limit| self.steps >= limit) {
+                return RunOutcome::StepLimit;
+            }
+            if let Err(fault) = self.step_observed(observer) {
+                return RunOutcome::Fault{pc: self.state.pc, fault};
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: &str = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
+
+    fn console(program: &str) -> Vm {
+        let instructions: Vec<Instruction> = program.lines().map(|line| line.parse().unwrap()).collect();
+        Vm::console(&instructions)
+    }
+
+    #[test]
+    fn parse_op() {
+        assert_eq!("jnz 1 -2".parse::<Op>().unwrap(), Op{mnemonic: String::from("jnz"), args: vec![1, -2]});
+        assert_eq!("acc +3".parse::<Op>().unwrap(), Op::from(&"acc +3".parse::<Instruction>().unwrap()));
+        assert!("acc x".parse::<Op>().is_err());
+        assert!("".parse::<Op>().is_err());
+    }
+
+    #[test]
+    fn display_op() {
+        assert_eq!("jnz 1 -2".parse::<Op>().unwrap().to_string(), "jnz +1 -2");
+        assert_eq!("acc +3".parse::<Op>().unwrap().to_string(), "acc +3");
+    }
+
+    #[test]
+    fn loop_outcome() {
+        let mut vm = console(PROGRAM);
+        assert_eq!(vm.run(&Halting::default()), RunOutcome::Loop{pc: 1});
+        assert_eq!(vm.state().accumulator(), 5);
+        assert_eq!(vm.steps(), 7);
+    }
+
+    #[test]
+    fn terminated_outcome() {
+        let mut vm = console(&PROGRAM.replace("jmp -4", "nop -4"));
+        assert_eq!(vm.run(&Halting::default()), RunOutcome::Terminated);
+        assert_eq!(vm.state().accumulator(), 8);
+    }
+
+    #[test]
+    fn crashed_outcome() {
+        assert_eq!(console("acc +1\njmp +5").run(&Halting::default()), RunOutcome::Crashed{pc: 6});
+        let lenient = Halting{strict_bounds: false, ..Halting::default()};
+        assert_eq!(console("acc +1\njmp +5").run(&lenient), RunOutcome::Terminated);
+    }
+
+    #[test]
+    fn step_limit() {
+        let mut vm = console(PROGRAM);
+        let halting = Halting{detect_loops: false, step_limit: Some(20), strict_bounds: true};
+        assert_eq!(vm.run(&halting), RunOutcome::StepLimit);
+        assert_eq!(vm.steps(), 20);
+    }
+
+    #[test]
+    fn unknown_instruction() {
+        let program = vec!["acc +1".parse().unwrap(), "mul 2".parse().unwrap()];
+        let error = Vm::new(program, &InstructionTable::standard(), 1).err().unwrap();
+        assert_eq!(error, LoadError{address: 1, mnemonic: String::from("mul")});
+        let wrong_arity = vec!["jmp 1 2".parse().unwrap()];
+        assert!(Vm::new(wrong_arity, &InstructionTable::standard(), 1).is_err());
+    }
+
+    #[test]
+    fn custom_instructions() {
+        // Multiplies register 1 into the accumulator until register 1 counts down to 0
+        let mut table = InstructionTable::standard();
+        table.register("set", 2, |state, args| {
+            *state.register_mut(args[0])? = args[1];
+            state.pc += 1;
+            Ok(())
+        });
+        table.register("mul", 1, |state, args| {
+            let factor = state.register(args[0])?;
+            state.registers[ACCUMULATOR] = state.accumulator().checked_mul(factor).ok_or(Fault::Overflow)?;
+            state.pc += 1;
+            Ok(())
+        });
+        table.register("dec", 1, |state, args| {
+            *state.register_mut(args[0])? -= 1;
+            state.pc += 1;
+            Ok(())
+        });
+        table.register("jnz", 2, |state, args| {
+            state.pc += if state.register(args[0])? != 0 { args[1] } else { 1 };
+            Ok(())
+        });
+        let program: Vec<Op> = "acc +1\nset 1 5\nmul 1\ndec 1\njnz 1 -2"
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect();
+        let mut vm = Vm::new(program, &table, 2).unwrap();
+        let halting = Halting{detect_loops: false, ..Halting::default()};
+        assert_eq!(vm.run(&halting), RunOutcome::Terminated);
+        assert_eq!(vm.state().registers, vec![120, 0]);
+        let mut faulty = Vm::new(vec!["acc +1".parse().unwrap(), "set 5 1".parse().unwrap()], &table, 2).unwrap();
+        assert_eq!(faulty.run(&halting), RunOutcome::Fault{pc: 1, fault: Fault::InvalidRegister(5)});
+        assert_eq!(faulty.state().registers, vec![1, 0]);
+        assert_eq!(faulty.steps(), 1);
+    }
+
+    #[test]
+    fn overflow_fault() {
+        let mut vm = console("acc +9223372036854775807\nacc +1");
+        assert_eq!(vm.run(&Halting::default()), RunOutcome::Fault{pc: 1, fault: Fault::Overflow});
+        assert_eq!(vm.state().accumulator(), i64::MAX);
+        assert_eq!(vm.step(), Err(Fault::Overflow));
+    }
+}
@@ -0,0 +1,37 @@
+use std::{fmt, cmp, clone};
+use crate::expression::token::Operator;
+use crate::expression::error::ExpressionError;
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub enum Expr {
+    Number(i64),
+    /// `column` is where the operator is written, for reporting errors
+    Binary {
+        operator: Operator,
+        column: usize,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+impl Expr {
+    /// Fails instead of overflowing or dividing by zero, pointing at the operator at fault
+    pub fn evaluate(&self) -> Result<i64, ExpressionError> {
+        match self {
+            Expr::Number(value) => Ok(*value),
+            Expr::Binary{operator, column, left, right} => operator
+                .apply(left.evaluate()?, right.evaluate()?)
+                .map_err(|kind| ExpressionError{column: *column, kind}),
+        }
+    }
+}
+
+/// Parenthesises every operation, e.g. `((1 + 2) * 3)`
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(value) => write!(f, "{}", value),
+            Expr::Binary{operator, left, right, ..} => write!(f, "({} {} {})", left, operator, right),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use std::{fmt, cmp, clone};
+
+/// What went wrong in an expression, at a column counted from 1
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub struct ExpressionError {
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
+pub enum ErrorKind {
+    UnexpectedCharacter(char),
+    NumberTooLarge,
+    ExpectedOperand,
+    ExpectedOperator,
+    UnclosedParenthesis,
+    UnmatchedParenthesis,
+    /// Parentheses or operators nest deeper than `parser::MAX_DEPTH`
+    TooDeep,
+    /// The operator has no entry in the precedence table
+    UnsupportedOperator(char),
+    Overflow,
+    DivisionByZero,
+    NegativeExponent,
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: ", self.column)?;
+        match &self.kind {
+            ErrorKind::UnexpectedCharacter(character) => write!(f, "unexpected character `{}`", character),
+            ErrorKind::NumberTooLarge => write!(f, "number does not fit in 64 bits"),
+            ErrorKind::ExpectedOperand => write!(f, "expected a number or `(`"),
+            ErrorKind::ExpectedOperator => write!(f, "expected an operator"),
+            ErrorKind::UnclosedParenthesis => write!(f, "`(` is never closed"),
+            ErrorKind::UnmatchedParenthesis => write!(f, "`)` has no matching `(`"),
+            ErrorKind::TooDeep => write!(f, "nested too deeply"),
+            ErrorKind::UnsupportedOperator(operator) => write!(f, "`{}` is not supported", operator),
+            ErrorKind::Overflow => write!(f, "overflow"),
+            ErrorKind::DivisionByZero => write!(f, "division by zero"),
+            ErrorKind::NegativeExponent => write!(f, "negative exponent"),
+        }
+    }
+}
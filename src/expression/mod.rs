@@ -0,0 +1,4 @@
+pub mod error;
+pub mod token;
+pub mod ast;
+pub mod parser;
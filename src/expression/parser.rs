@@ -0,0 +1,224 @@
+use std::{fmt, cmp, clone, marker, default};
+use std::collections::HashMap;
+use crate::expression::token::{tokenize, Operator, Token, TokenKind};
+use crate::expression::ast::Expr;
+use crate::expression::error::{ExpressionError, ErrorKind};
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a ^ b ^ c` is `a ^ (b ^ c)`
+    Right,
+}
+
+/// How tightly each operator binds, higher binding tighter. Operators missing from the
+/// table are rejected when parsing.
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, default::Default)]
+pub struct PrecedenceTable {
+    operators: HashMap<Operator, (u8, Associativity)>,
+}
+
+impl PrecedenceTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Part 1 of the puzzle: `+` and `*` bind equally
+    pub fn basic() -> Self {
+        let mut table = PrecedenceTable::new();
+        table.set(Operator::Add, 1, Associativity::Left);
+        table.set(Operator::Multiply, 1, Associativity::Left);
+        table
+    }
+    /// Part 2 of the puzzle: `+` binds tighter than `*`
+    pub fn advanced() -> Self {
+        let mut table = PrecedenceTable::new();
+        table.set(Operator::Add, 2, Associativity::Left);
+        table.set(Operator::Multiply, 1, Associativity::Left);
+        table
+    }
+    /// The usual rules: `^` binds tightest and to the right, then `*`, `/` and `%`, then `+` and `-`
+    pub fn arithmetic() -> Self {
+        let mut table = PrecedenceTable::new();
+        table.set(Operator::Add, 1, Associativity::Left);
+        table.set(Operator::Subtract, 1, Associativity::Left);
+        table.set(Operator::Multiply, 2, Associativity::Left);
+        table.set(Operator::Divide, 2, Associativity::Left);
+        table.set(Operator::Remainder, 2, Associativity::Left);
+        table.set(Operator::Power, 3, Associativity::Right);
+        table
+    }
+    /// Adds or replaces the rule for `operator`
+    pub fn set(&mut self, operator: Operator, precedence: u8, associativity: Associativity) {
+        self.operators.insert(operator, (precedence, associativity));
+    }
+    pub fn get(&self, operator: Operator) -> Option<(u8, Associativity)> {
+        self.operators.get(&operator).copied()
+    }
+}
+
+/// How deep parentheses and operators may nest. The parser and the syntax tree both
+/// recurse once per level, so this keeps either from overflowing the stack.
+pub const MAX_DEPTH: usize = 1000;
+
+/// Pratt parser over the tokens of a single expression
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    /// Column just past the expression, where running out of tokens is reported
+    end: usize,
+    table: &'a PrecedenceTable,
+    /// Calls to `expression` currently running
+    nesting: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).copied();
+        self.position += 1;
+        token
+    }
+    /// Parses an expression starting at the token at `column`, along with the depth of its tree
+    fn nested(&mut self, column: usize, min_precedence: u16) -> Result<(Expr, usize), ExpressionError> {
+        if self.nesting == MAX_DEPTH {
+            return Err(ExpressionError{column, kind: ErrorKind::TooDeep});
+        }
+        self.nesting += 1;
+        let parsed = self.expression(min_precedence)?;
+        self.nesting -= 1;
+        Ok(parsed)
+    }
+    fn expression(&mut self, min_precedence: u16) -> Result<(Expr, usize), ExpressionError> {
+        let (mut left, mut depth) = self.operand()?;
+        while let Some(token) = self.tokens.get(self.position).copied() {
+            let operator = match token.kind {
+                TokenKind::Operator(operator) => operator,
+                TokenKind::Close => break,
+                _ => return Err(ExpressionError{column: token.column, kind: ErrorKind::ExpectedOperator}),
+            };
+            let (precedence, associativity) = self.table.get(operator)
+                .ok_or(ExpressionError{column: token.column, kind: ErrorKind::UnsupportedOperator(operator.symbol())})?;
+            let precedence = u16::from(precedence);
+            if precedence < min_precedence {
+                break;
+            }
+            self.position += 1;
+            // The right side only takes operators that bind tighter for left associativity,
+            // so `a - b - c` groups as `(a - b) - c`, and equally tight ones too for right
+            // associativity, so `a ^ b ^ c` groups as `a ^ (b ^ c)`
+            let (right, right_depth) = self.nested(token.column, match associativity {
+                Associativity::Left => precedence + 1,
+                Associativity::Right => precedence,
+            })?;
+            depth = 1 + depth.max(right_depth);
+            if depth > MAX_DEPTH {
+                return Err(ExpressionError{column: token.column, kind: ErrorKind::TooDeep});
+            }
+            left = Expr::Binary{operator, column: token.column, left: Box::new(left), right: Box::new(right)};
+        }
+        Ok((left, depth))
+    }
+    fn operand(&mut self) -> Result<(Expr, usize), ExpressionError> {
+        match self.next() {
+            Some(Token{kind: TokenKind::Number(value), ..}) => Ok((Expr::Number(value), 0)),
+            Some(Token{kind: TokenKind::Open, column}) => {
+                let inner = self.nested(column, 0)?;
+                match self.next() {
+                    Some(Token{kind: TokenKind::Close, ..}) => Ok(inner),
+                    _ => Err(ExpressionError{column, kind: ErrorKind::UnclosedParenthesis}),
+                }
+            },
+            Some(token) => Err(ExpressionError{column: token.column, kind: ErrorKind::ExpectedOperand}),
+            None => Err(ExpressionError{column: self.end, kind: ErrorKind::ExpectedOperand}),
+        }
+    }
+}
+
+pub fn parse(expression: &str, table: &PrecedenceTable) -> Result<Expr, ExpressionError> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser{
+        tokens: &tokens,
+        position: 0,
+        end: expression.chars().count() + 1,
+        table,
+        nesting: 0,
+    };
+    let (parsed, _depth) = parser.expression(0)?;
+    match parser.next() {
+        Some(token) => Err(ExpressionError{column: token.column, kind: ErrorKind::UnmatchedParenthesis}),
+        None => Ok(parsed),
+    }
+}
+
+/// Parses and evaluates with checked arithmetic
+pub fn evaluate(expression: &str, table: &PrecedenceTable) -> Result<i64, ExpressionError> {
+    parse(expression, table)?.evaluate()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(expression: &str) -> ExpressionError {
+        evaluate(expression, &PrecedenceTable::basic()).err().unwrap()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(parse("1 + 2 * 3 + 4", &PrecedenceTable::basic()).unwrap().to_string(), "(((1 + 2) * 3) + 4)");
+        assert_eq!(parse("1 + 2 * 3 + 4", &PrecedenceTable::advanced()).unwrap().to_string(), "((1 + 2) * (3 + 4))");
+        assert_eq!(parse("2 * (3 * 4) + 5", &PrecedenceTable::advanced()).unwrap().to_string(), "(2 * ((3 * 4) + 5))");
+        assert_eq!(evaluate("((7))", &PrecedenceTable::basic()), Ok(7));
+    }
+
+    #[test]
+    fn errors_point_at_column() {
+        assert_eq!(error("1 + "), ExpressionError{column: 5, kind: ErrorKind::ExpectedOperand});
+        assert_eq!(error("1 + * 2"), ExpressionError{column: 5, kind: ErrorKind::ExpectedOperand});
+        assert_eq!(error("1 2"), ExpressionError{column: 3, kind: ErrorKind::ExpectedOperator});
+        assert_eq!(error("1 + (2 * 3"), ExpressionError{column: 5, kind: ErrorKind::UnclosedParenthesis});
+        assert_eq!(error("1 + 2) * 3"), ExpressionError{column: 6, kind: ErrorKind::UnmatchedParenthesis});
+        assert_eq!(error("()"), ExpressionError{column: 2, kind: ErrorKind::ExpectedOperand});
+        assert_eq!(error("").to_string(), "column 1: expected a number or `(`");
+    }
+
+    #[test]
+    fn custom_tables() {
+        let arithmetic = PrecedenceTable::arithmetic();
+        assert_eq!(parse("1 - 2 - 3 * 4 ^ 3 ^ 2", &arithmetic).unwrap().to_string(), "((1 - 2) - (3 * (4 ^ (3 ^ 2))))");
+        assert_eq!(evaluate("7 + 10 % 4 * 3 - 8 / 3", &arithmetic), Ok(11));
+        let mut inverted = PrecedenceTable::new();
+        inverted.set(Operator::Add, 1, Associativity::Right);
+        inverted.set(Operator::Multiply, 2, Associativity::Right);
+        assert_eq!(parse("1 + 2 * 3 * 4 + 5", &inverted).unwrap().to_string(), "(1 + ((2 * (3 * 4)) + 5))");
+        assert_eq!(error("1 + 2 - 3"), ExpressionError{column: 7, kind: ErrorKind::UnsupportedOperator('-')});
+    }
+
+    #[test]
+    fn arithmetic_errors() {
+        let arithmetic = PrecedenceTable::arithmetic();
+        assert_eq!(evaluate("1 + 6 / (3 - 3)", &arithmetic), Err(ExpressionError{column: 7, kind: ErrorKind::DivisionByZero}));
+        assert_eq!(evaluate("2 ^ (1 - 2)", &arithmetic), Err(ExpressionError{column: 3, kind: ErrorKind::NegativeExponent}));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let deep = format!("{}1{}", "(".repeat(100_000), ")".repeat(100_000));
+        assert_eq!(error(&deep), ExpressionError{column: MAX_DEPTH + 1, kind: ErrorKind::TooDeep});
+        let just_deep_enough = format!("{}1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(evaluate(&just_deep_enough, &PrecedenceTable::basic()), Ok(1));
+        // Each `+` adds a level to the tree even without any recursion in the parser
+        let long = format!("1{}", " + 1".repeat(MAX_DEPTH + 1));
+        assert_eq!(error(&long), ExpressionError{column: 4 * MAX_DEPTH + 3, kind: ErrorKind::TooDeep});
+        assert_eq!(evaluate(&format!("1{}", " + 1".repeat(MAX_DEPTH)), &PrecedenceTable::basic()), Ok(MAX_DEPTH as i64 + 1));
+        let mut right = PrecedenceTable::new();
+        right.set(Operator::Add, 1, Associativity::Right);
+        assert_eq!(evaluate(&"1 + ".repeat(100_000), &right).unwrap_err().kind, ErrorKind::TooDeep);
+    }
+
+    #[test]
+    fn overflow() {
+        assert_eq!(error("1 + 3037000500 * 3037000500"), ExpressionError{column: 16, kind: ErrorKind::Overflow});
+        assert_eq!(evaluate("9223372036854775807 * 1", &PrecedenceTable::basic()), Ok(i64::MAX));
+    }
+}
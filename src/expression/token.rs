@@ -0,0 +1,147 @@
+use std::{fmt, cmp, clone, marker, hash};
+use std::convert::TryFrom;
+use crate::expression::error::{ExpressionError, ErrorKind};
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    /// Exponentiation, written `^`
+    Power,
+    Remainder,
+}
+
+impl Operator {
+    pub const ALL: [Operator; 6] = [
+        Operator::Add,
+        Operator::Subtract,
+        Operator::Multiply,
+        Operator::Divide,
+        Operator::Power,
+        Operator::Remainder,
+    ];
+    /// Checked arithmetic. Division and remainder truncate towards zero.
+    pub fn apply(&self, left: i64, right: i64) -> Result<i64, ErrorKind> {
+        let result = match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide | Operator::Remainder if right == 0 => return Err(ErrorKind::DivisionByZero),
+            Operator::Divide => left.checked_div(right),
+            Operator::Remainder => left.checked_rem(right),
+            Operator::Power if right < 0 => return Err(ErrorKind::NegativeExponent),
+            // The only bases whose powers stay in range for exponents past `u32`
+            Operator::Power if left == 0 || left == 1 => Some(if right == 0 { 1 } else { left }),
+            Operator::Power if left == -1 => Some(if right % 2 == 0 { 1 } else { -1 }),
+            Operator::Power => u32::try_from(right).ok().and_then(|exponent| left.checked_pow(exponent)),
+        };
+        result.ok_or(ErrorKind::Overflow)
+    }
+    pub fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+            Operator::Power => '^',
+            Operator::Remainder => '%',
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub enum TokenKind {
+    Number(i64),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+/// A token and the column it starts at, counted from 1
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub column: usize,
+}
+
+pub fn tokenize(expression: &str) -> Result<Vec<Token>, ExpressionError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut characters = expression.chars().enumerate().peekable();
+    while let Some((i, character)) = characters.next() {
+        let column = i + 1;
+        let kind = match character {
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            '0'..='9' => {
+                let mut digits = character.to_string();
+                while let Some((_i, digit)) = characters.next_if(|(_i, next)| next.is_ascii_digit()) {
+                    digits.push(digit);
+                }
+                TokenKind::Number(digits.parse().or(Err(ExpressionError{column, kind: ErrorKind::NumberTooLarge}))?)
+            },
+            c if c.is_whitespace() => continue,
+            c => match Operator::ALL.iter().find(|operator| operator.symbol() == c) {
+                Some(operator) => TokenKind::Operator(*operator),
+                None => return Err(ExpressionError{column, kind: ErrorKind::UnexpectedCharacter(c)}),
+            },
+        };
+        tokens.push(Token{kind, column});
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let kinds: Vec<TokenKind> = tokenize("12 *(3+ 4)").unwrap().iter().map(|token| token.kind).collect();
+        assert_eq!(kinds, vec![
+            TokenKind::Number(12),
+            TokenKind::Operator(Operator::Multiply),
+            TokenKind::Open,
+            TokenKind::Number(3),
+            TokenKind::Operator(Operator::Add),
+            TokenKind::Number(4),
+            TokenKind::Close,
+        ]);
+        let columns: Vec<usize> = tokenize("12 *(3+ 4)").unwrap().iter().map(|token| token.column).collect();
+        assert_eq!(columns, vec![1, 4, 5, 6, 7, 9, 10]);
+        let operators: Vec<TokenKind> = tokenize("+-*/^%").unwrap().iter().map(|token| token.kind).collect();
+        assert_eq!(operators, Operator::ALL.iter().map(|operator| TokenKind::Operator(*operator)).collect::<Vec<TokenKind>>());
+    }
+
+    #[test]
+    fn apply() {
+        assert_eq!(Operator::Subtract.apply(2, 5), Ok(-3));
+        assert_eq!(Operator::Divide.apply(-7, 2), Ok(-3));
+        assert_eq!(Operator::Remainder.apply(-7, 2), Ok(-1));
+        assert_eq!(Operator::Power.apply(-2, 3), Ok(-8));
+        assert_eq!(Operator::Divide.apply(1, 0), Err(ErrorKind::DivisionByZero));
+        assert_eq!(Operator::Remainder.apply(1, 0), Err(ErrorKind::DivisionByZero));
+        assert_eq!(Operator::Power.apply(2, -1), Err(ErrorKind::NegativeExponent));
+        assert_eq!(Operator::Power.apply(2, 63), Err(ErrorKind::Overflow));
+        assert_eq!(Operator::Power.apply(2, 5_000_000_000), Err(ErrorKind::Overflow));
+        assert_eq!(Operator::Power.apply(1, 5_000_000_000), Ok(1));
+        assert_eq!(Operator::Power.apply(0, 5_000_000_000), Ok(0));
+        assert_eq!(Operator::Power.apply(0, 0), Ok(1));
+        assert_eq!(Operator::Power.apply(-1, 5_000_000_000), Ok(1));
+        assert_eq!(Operator::Power.apply(-1, 5_000_000_001), Ok(-1));
+        assert_eq!(Operator::Divide.apply(i64::MIN, -1), Err(ErrorKind::Overflow));
+    }
+
+    #[test]
+    fn invalid_tokens() {
+        assert_eq!(tokenize("1 + x").err(), Some(ExpressionError{column: 5, kind: ErrorKind::UnexpectedCharacter('x')}));
+        assert_eq!(tokenize("2 * 99999999999999999999").err(), Some(ExpressionError{column: 5, kind: ErrorKind::NumberTooLarge}));
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fmt, cmp, clone, hash, marker, str};
+use std::collections::{HashMap, HashSet};
+
+/// Position in a space an `Automaton` can run on
+pub trait Cell: marker::Copy + cmp::Eq + hash::Hash {
+    /// Calls `f` with every neighbour and how much this cell adds to that neighbour's count
+    fn for_each_neighbour<F: FnMut(Self, usize)>(&self, f: F);
+    /// How many real cells this one stands for, for spaces stored in a reduced form
+    fn multiplicity(&self) -> usize {
+        1
+    }
+}
+
+/// Moore neighbourhood: every cell differing by at most 1 on each axis
+impl<const N: usize> Cell for [i32; N] {
+    fn for_each_neighbour<F: FnMut(Self, usize)>(&self, mut f: F) {
+        let mut offset = [-1i32; N];
+        loop {
+            if offset.iter().any(|d| *d != 0) {
+                let mut neighbour = *self;
+                for (axis, d) in offset.iter().enumerate() {
+                    neighbour[axis] += d;
+                }
+                f(neighbour, 1);
+            }
+            let mut axis = 0;
+            loop {
+                if axis == N {
+                    return;
+                }
+                if offset[axis] < 1 {
+                    offset[axis] += 1;
+                    break;
+                }
+                offset[axis] = -1;
+                axis += 1;
+            }
+        }
+    }
+}
+
+/// Neighbour counts at which a dead cell comes alive, and at which a live cell stays alive
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq)]
+pub struct Rule {
+    birth: Vec<usize>,
+    survive: Vec<usize>,
+}
+
+impl Rule {
+    pub fn new(birth: &[usize], survive: &[usize]) -> Self {
+        Rule{
+            birth: birth.to_vec(),
+            survive: survive.to_vec(),
+        }
+    }
+    /// B3/S23
+    pub fn conway() -> Self {
+        Rule::new(&[3], &[2, 3])
+    }
+    pub fn is_alive_next(&self, alive: bool, neighbours: usize) -> bool {
+        match alive {
+            true => self.survive.contains(&neighbours),
+            false => self.birth.contains(&neighbours),
+        }
+    }
+}
+
+#[derive(fmt::Debug)]
+pub struct ParseRuleError;
+
+/// Parses rules in `B3/S23` notation
+impl str::FromStr for Rule {
+    type Err = ParseRuleError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('/').collect();
+        if parts.len() != 2 || !parts[0].starts_with('B') || !parts[1].starts_with('S') {
+            return Err(ParseRuleError);
+        }
+        let counts = |part: &str| part.chars()
+            .skip(1)
+            .map(|c| c.to_digit(10).map(|d| d as usize).ok_or(ParseRuleError))
+            .collect::<Result<Vec<usize>, ParseRuleError>>();
+        Ok(Rule{
+            birth: counts(parts[0])?,
+            survive: counts(parts[1])?,
+        })
+    }
+}
+
+/// Sparse Life-like cellular automaton. Only live cells are stored, and neighbour
+/// counts are accumulated from them, so the cost of a generation follows the population.
+#[derive(fmt::Debug, clone::Clone)]
+pub struct Automaton<C: Cell> {
+    active: HashSet<C>,
+    rule: Rule,
+    generation: usize,
+}
+
+impl<C: Cell> Automaton<C> {
+    pub fn new<I: IntoIterator<Item = C>>(active: I, rule: Rule) -> Self {
+        Automaton{
+            active: active.into_iter().collect(),
+            rule,
+            generation: 0,
+        }
+    }
+    pub fn step(&mut self) {
+        let mut counts: HashMap<C, usize> = HashMap::new();
+        for cell in &self.active {
+            cell.for_each_neighbour(|neighbour, weight| {
+                *counts.entry(neighbour).or_insert(0) += weight;
+            });
+        }
+        let mut next: HashSet<C> = counts.iter()
+            .filter(|(cell, count)| self.rule.is_alive_next(self.active.contains(cell), **count))
+            .map(|(cell, _count)| *cell)
+            .collect();
+        if self.rule.is_alive_next(true, 0) {
+            next.extend(self.active.iter().filter(|cell| !counts.contains_key(cell)));
+        }
+        self.active = next;
+        self.generation += 1;
+    }
+    pub fn run(&mut self, cycles: usize) {
+        for _i in 0..cycles {
+            self.step();
+        }
+    }
+    pub fn active(&self) -> &HashSet<C> {
+        &self.active
+    }
+    pub fn is_active(&self, cell: &C) -> bool {
+        self.active.contains(cell)
+    }
+    /// Number of live cells, counting each stored cell by its multiplicity
+    pub fn population(&self) -> usize {
+        self.active.iter()
+            .map(|cell| cell.multiplicity())
+            .sum()
+    }
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn neighbours_of_origin<const N: usize>() -> HashSet<[i32; N]> {
+        let mut neighbours: HashSet<[i32; N]> = HashSet::new();
+        [0; N].for_each_neighbour(|neighbour, weight| {
+            assert_eq!(weight, 1);
+            assert!(neighbours.insert(neighbour));
+        });
+        neighbours
+    }
+
+    #[test]
+    fn neighbours() {
+        assert_eq!(neighbours_of_origin::<1>().len(), 2);
+        assert_eq!(neighbours_of_origin::<2>().len(), 8);
+        assert_eq!(neighbours_of_origin::<3>().len(), 26);
+        assert_eq!(neighbours_of_origin::<4>().len(), 80);
+        let neighbours = neighbours_of_origin::<3>();
+        assert!(!neighbours.contains(&[0, 0, 0]));
+        assert!(neighbours.iter().all(|n| n.iter().all(|d| d.abs() <= 1)));
+    }
+
+    #[test]
+    fn parse_rule() {
+        assert_eq!("B3/S23".parse::<Rule>().unwrap(), Rule::conway());
+        assert_eq!("B36/S".parse::<Rule>().unwrap(), Rule::new(&[3, 6], &[]));
+        assert!("S23/B3".parse::<Rule>().is_err());
+        assert!("B3x/S23".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn blinker() {
+        let mut automaton = Automaton::new(vec![[0, 1], [1, 1], [2, 1]], Rule::conway());
+        automaton.step();
+        let expected: HashSet<[i32; 2]> = vec![[1, 0], [1, 1], [1, 2]].into_iter().collect();
+        assert_eq!(*automaton.active(), expected);
+        automaton.step();
+        assert!(automaton.is_active(&[0, 1]));
+        assert_eq!(automaton.population(), 3);
+        assert_eq!(automaton.generation(), 2);
+    }
+
+    #[test]
+    fn block_is_still() {
+        let block = vec![[0, 0], [0, 1], [1, 0], [1, 1]];
+        let mut automaton = Automaton::new(block.clone(), Rule::conway());
+        automaton.run(5);
+        assert_eq!(*automaton.active(), block.into_iter().collect());
+    }
+
+    #[test]
+    fn isolated_cells_survive_with_zero_rule() {
+        let mut automaton = Automaton::new(vec![[0, 0, 0]], Rule::new(&[], &[0]));
+        automaton.run(3);
+        assert_eq!(automaton.population(), 1);
+    }
+}
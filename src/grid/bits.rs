@@ -0,0 +1,334 @@
+use std::{fmt, cmp, clone, hash, ops};
+use crate::grid::grid::{Grid, GridOutOfBoundsError};
+use crate::grid::coordinate::CartesianCoordinate;
+use crate::grid::automaton::Rule;
+
+const WORD: usize = 64;
+
+/// Grid of two-state cells packed 64 to a word. Every row starts on a new word and the
+/// bits past `width` are always zero, so whole-grid operations can work word by word.
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq, hash::Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD);
+        BitGrid{
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    fn index(&self, pos: &CartesianCoordinate) -> Result<(usize, u64), GridOutOfBoundsError> {
+        if pos.x >= self.width || pos.y >= self.height {
+            return Err(GridOutOfBoundsError);
+        }
+        Ok((pos.y * self.words_per_row + pos.x / WORD, 1 << (pos.x % WORD)))
+    }
+    pub fn get(&self, pos: &CartesianCoordinate) -> Result<bool, GridOutOfBoundsError> {
+        let (word, bit) = self.index(pos)?;
+        Ok(self.words[word] & bit != 0)
+    }
+    pub fn set(&mut self, pos: &CartesianCoordinate, value: bool) -> Result<(), GridOutOfBoundsError> {
+        let (word, bit) = self.index(pos)?;
+        match value {
+            true => self.words[word] |= bit,
+            false => self.words[word] &= !bit,
+        }
+        Ok(())
+    }
+    pub fn toggle(&mut self, pos: &CartesianCoordinate) -> Result<bool, GridOutOfBoundsError> {
+        let (word, bit) = self.index(pos)?;
+        self.words[word] ^= bit;
+        Ok(self.words[word] & bit != 0)
+    }
+    pub fn count_ones(&self) -> usize {
+        self.words.iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+    fn row(&self, y: usize) -> &[u64] {
+        &self.words[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+    fn clear_padding(&mut self) {
+        if self.width.is_multiple_of(WORD) {
+            return;
+        }
+        let mask = (1u64 << (self.width % WORD)) - 1;
+        for y in 0..self.height {
+            self.words[(y + 1) * self.words_per_row - 1] &= mask;
+        }
+    }
+    /// Moves every cell `n` rows down, or up for negative `n`. Cells shifted in are off.
+    pub fn shift_rows(&self, n: isize) -> BitGrid {
+        let mut shifted = BitGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            let source = y as isize - n;
+            if source >= 0 && (source as usize) < self.height {
+                let start = y * self.words_per_row;
+                shifted.words[start..start + self.words_per_row].copy_from_slice(self.row(source as usize));
+            }
+        }
+        shifted
+    }
+    /// Moves every cell `n` columns right, or left for negative `n`. Cells shifted in are off.
+    pub fn shift_columns(&self, n: isize) -> BitGrid {
+        let mut shifted = BitGrid::new(self.width, self.height);
+        let word_shift = n.unsigned_abs() / WORD;
+        let bit_shift = n.unsigned_abs() % WORD;
+        for y in 0..self.height {
+            let row = self.row(y);
+            let start = y * self.words_per_row;
+            for i in 0..self.words_per_row {
+                shifted.words[start + i] = match n >= 0 {
+                    true => {
+                        let low = i.checked_sub(word_shift).map_or(0, |j| row[j]);
+                        let carry = i.checked_sub(word_shift + 1).map_or(0, |j| row[j]);
+                        match bit_shift {
+                            0 => low,
+                            _ => low << bit_shift | carry >> (WORD - bit_shift),
+                        }
+                    },
+                    false => {
+                        let high = row.get(i + word_shift).copied().unwrap_or(0);
+                        let carry = row.get(i + word_shift + 1).copied().unwrap_or(0);
+                        match bit_shift {
+                            0 => high,
+                            _ => high >> bit_shift | carry << (WORD - bit_shift),
+                        }
+                    },
+                };
+            }
+        }
+        shifted.clear_padding();
+        shifted
+    }
+    /// Live neighbour count of every cell as four bit planes, least significant first
+    fn neighbour_counts(&self) -> [Vec<u64>; 4] {
+        let left = self.shift_columns(-1);
+        let right = self.shift_columns(1);
+        let neighbours = [
+            left.shift_rows(-1), self.shift_rows(-1), right.shift_rows(-1),
+            left.shift_rows(1), self.shift_rows(1), right.shift_rows(1),
+            left, right,
+        ];
+        let mut planes = [vec![0u64; self.words.len()], vec![0u64; self.words.len()],
+            vec![0u64; self.words.len()], vec![0u64; self.words.len()]];
+        for neighbour in neighbours.iter() {
+            for (i, word) in neighbour.words.iter().enumerate() {
+                let mut carry = *word;
+                for plane in planes.iter_mut() {
+                    let sum = plane[i] ^ carry;
+                    carry &= plane[i];
+                    plane[i] = sum;
+                }
+            }
+        }
+        planes
+    }
+    /// One generation of a Life-like rule. Cells outside the grid are always off.
+    pub fn step(&self, rule: &Rule) -> BitGrid {
+        let planes = self.neighbour_counts();
+        let mut next = BitGrid::new(self.width, self.height);
+        for (i, word) in self.words.iter().enumerate() {
+            let mut result = 0u64;
+            for count in 0..=8usize {
+                let alive = rule.is_alive_next(true, count);
+                let born = rule.is_alive_next(false, count);
+                if !alive && !born {
+                    continue;
+                }
+                let matches = planes.iter()
+                    .enumerate()
+                    .fold(!0u64, |acc, (bit, plane)| match count >> bit & 1 {
+                        1 => acc & plane[i],
+                        _ => acc & !plane[i],
+                    });
+                if alive {
+                    result |= matches & word;
+                }
+                if born {
+                    result |= matches & !word;
+                }
+            }
+            next.words[i] = result;
+        }
+        next.clear_padding();
+        next
+    }
+    pub fn from_grid<T, F>(grid: &Grid<T>, predicate: F) -> BitGrid
+        where F: Fn(&T) -> bool {
+        let width = grid.rows().iter().map(Vec::len).max().unwrap_or(0);
+        let mut bits = BitGrid::new(width, grid.height());
+        for (y, row) in grid.rows().iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if predicate(cell) {
+                    bits.set(&CartesianCoordinate::new(x, y), true).unwrap();
+                }
+            }
+        }
+        bits
+    }
+    pub fn to_grid<T: clone::Clone>(&self, on: T, off: T) -> Grid<T> {
+        Grid::new((0..self.height)
+            .map(|y| (0..self.width)
+                .map(|x| match self.get(&CartesianCoordinate::new(x, y)).unwrap() {
+                    true => on.clone(),
+                    false => off.clone(),
+                })
+                .collect())
+            .collect())
+    }
+}
+
+impl ops::BitAnd for &BitGrid {
+    type Output = BitGrid;
+    fn bitand(self, other: &BitGrid) -> BitGrid {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        let mut result = self.clone();
+        result.words.iter_mut().zip(other.words.iter()).for_each(|(a, b)| *a &= b);
+        result
+    }
+}
+
+impl ops::BitOr for &BitGrid {
+    type Output = BitGrid;
+    fn bitor(self, other: &BitGrid) -> BitGrid {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        let mut result = self.clone();
+        result.words.iter_mut().zip(other.words.iter()).for_each(|(a, b)| *a |= b);
+        result
+    }
+}
+
+impl ops::Not for &BitGrid {
+    type Output = BitGrid;
+    fn not(self) -> BitGrid {
+        let mut result = self.clone();
+        result.words.iter_mut().for_each(|word| *word = !*word);
+        result.clear_padding();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::grid::Neighbourhood;
+
+    fn pseudo_random(width: usize, height: usize, seed: u64) -> BitGrid {
+        let mut state = seed;
+        let mut bits = BitGrid::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                bits.set(&CartesianCoordinate::new(x, y), state >> 62 == 0).unwrap();
+            }
+        }
+        bits
+    }
+
+    fn naive_step(grid: &Grid<bool>, rule: &Rule) -> Grid<bool> {
+        Grid::new(grid.rows().iter()
+            .enumerate()
+            .map(|(y, row)| row.iter()
+                .enumerate()
+                .map(|(x, alive)| {
+                    let count = grid.neighbours(&CartesianCoordinate::new(x, y), Neighbourhood::Eight)
+                        .iter()
+                        .filter(|pos| grid.at(pos).unwrap())
+                        .count();
+                    rule.is_alive_next(*alive, count)
+                })
+                .collect())
+            .collect())
+    }
+
+    #[test]
+    fn get_set_toggle() {
+        let mut bits = BitGrid::new(70, 2);
+        let pos = CartesianCoordinate::new(65, 1);
+        assert!(!bits.get(&pos).unwrap());
+        bits.set(&pos, true).unwrap();
+        assert!(bits.get(&pos).unwrap());
+        assert!(!bits.toggle(&pos).unwrap());
+        assert!(bits.set(&CartesianCoordinate::new(70, 0), true).is_err());
+        assert!(bits.get(&CartesianCoordinate::new(0, 2)).is_err());
+    }
+
+    #[test]
+    fn count_ones() {
+        let grid: Grid<char> = "#.#\n.##".parse().unwrap();
+        assert_eq!(BitGrid::from_grid(&grid, |c| *c == '#').count_ones(), 4);
+    }
+
+    #[test]
+    fn grid_round_trip() {
+        let grid: Grid<char> = "#..#\n.##.\n....".parse().unwrap();
+        let bits = BitGrid::from_grid(&grid, |c| *c == '#');
+        assert_eq!(bits.to_grid('#', '.'), grid);
+    }
+
+    #[test]
+    fn shift_rows() {
+        let grid: Grid<char> = "#..\n.#.\n..#".parse().unwrap();
+        let bits = BitGrid::from_grid(&grid, |c| *c == '#');
+        assert_eq!(bits.shift_rows(1).to_grid('#', '.'), "...\n#..\n.#.".parse().unwrap());
+        assert_eq!(bits.shift_rows(-2).to_grid('#', '.'), "..#\n...\n...".parse().unwrap());
+    }
+
+    #[test]
+    fn shift_columns_across_words() {
+        let bits = pseudo_random(150, 3, 7);
+        for n in &[-130isize, -64, -63, -1, 0, 1, 63, 64, 130] {
+            let shifted = bits.shift_columns(*n);
+            for y in 0..3 {
+                for x in 0..150isize {
+                    let source = x - n;
+                    let expected = (0..150).contains(&source)
+                        && bits.get(&CartesianCoordinate::new(source as usize, y)).unwrap();
+                    assert_eq!(shifted.get(&CartesianCoordinate::new(x as usize, y)).unwrap(), expected);
+                }
+            }
+            assert!(shifted.count_ones() <= bits.count_ones());
+        }
+    }
+
+    #[test]
+    fn step_matches_naive() {
+        let rules = [Rule::conway(), "B36/S23".parse().unwrap(), "B2/S".parse().unwrap(), Rule::new(&[], &[0, 8])];
+        for (width, height, seed) in &[(10, 10, 1), (64, 4, 2), (130, 6, 3)] {
+            let bits = pseudo_random(*width, *height, *seed);
+            for rule in rules.iter() {
+                let mut expected = bits.to_grid(true, false);
+                let mut actual = bits.clone();
+                for _i in 0..4 {
+                    expected = naive_step(&expected, rule);
+                    actual = actual.step(rule);
+                    assert_eq!(actual.to_grid(true, false), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bitwise() {
+        let a = BitGrid::from_grid(&"##.\n..#".parse::<Grid<char>>().unwrap(), |c| *c == '#');
+        let b = BitGrid::from_grid(&"#.#\n..#".parse::<Grid<char>>().unwrap(), |c| *c == '#');
+        assert_eq!((&a & &b).to_grid('#', '.'), "#..\n..#".parse().unwrap());
+        assert_eq!((&a | &b).to_grid('#', '.'), "###\n..#".parse().unwrap());
+        assert_eq!((!&a).count_ones(), 3);
+    }
+}
@@ -1,6 +1,6 @@
-use std::{fmt, cmp, clone, marker};
+use std::{fmt, cmp, clone, marker, hash, default, ops, convert};
 
-#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy)]
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash)]
 pub struct CartesianCoordinate {
     pub x: usize,
     pub y: usize,
@@ -12,3 +12,181 @@ impl CartesianCoordinate {
         }
     }
 }
+
+/// Signed position or offset. Like `Grid`, y grows downwards.
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash, default::Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+impl Point {
+    pub fn new(x: i64, y: i64) -> Self {
+        Point{
+            x, y
+        }
+    }
+    pub fn manhattan(&self, other: &Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+    pub fn chebyshev(&self, other: &Point) -> i64 {
+        cmp::max((self.x - other.x).abs(), (self.y - other.y).abs())
+    }
+    /// Rotates 90 degrees clockwise around the origin
+    pub fn rotate_right(&self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+    /// Rotates 90 degrees counter-clockwise around the origin
+    pub fn rotate_left(&self) -> Point {
+        Point::new(self.y, -self.x)
+    }
+}
+
+impl ops::Add for Point {
+    type Output = Point;
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+impl ops::AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+impl ops::Sub for Point {
+    type Output = Point;
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+impl ops::SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+impl ops::Mul<i64> for Point {
+    type Output = Point;
+    fn mul(self, scalar: i64) -> Point {
+        Point::new(self.x * scalar, self.y * scalar)
+    }
+}
+impl ops::Neg for Point {
+    type Output = Point;
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+#[derive(fmt::Debug)]
+pub struct NegativeCoordinateError;
+
+impl convert::From<CartesianCoordinate> for Point {
+    fn from(pos: CartesianCoordinate) -> Self {
+        Point::new(pos.x as i64, pos.y as i64)
+    }
+}
+impl convert::TryFrom<Point> for CartesianCoordinate {
+    type Error = NegativeCoordinateError;
+    fn try_from(point: Point) -> Result<Self, Self::Error> {
+        if point.x < 0 || point.y < 0 {
+            return Err(NegativeCoordinateError);
+        }
+        Ok(CartesianCoordinate::new(point.x as usize, point.y as usize))
+    }
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West
+}
+impl Direction {
+    pub fn all() -> [Direction; 4] {
+        [Direction::North, Direction::East, Direction::South, Direction::West]
+    }
+    pub fn turn_right(&self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+    pub fn turn_left(&self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::West => Direction::South,
+            Direction::South => Direction::East,
+            Direction::East => Direction::North,
+        }
+    }
+    pub fn reverse(&self) -> Direction {
+        self.turn_right().turn_right()
+    }
+    /// Unit step in this direction
+    pub fn offset(&self) -> Point {
+        match self {
+            Direction::North => Point::new(0, -1),
+            Direction::East => Point::new(1, 0),
+            Direction::South => Point::new(0, 1),
+            Direction::West => Point::new(-1, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn point_arithmetic() {
+        let mut point = Point::new(3, -2) + Point::new(-1, 5);
+        assert_eq!(point, Point::new(2, 3));
+        point -= Point::new(1, 1);
+        assert_eq!(point, Point::new(1, 2));
+        assert_eq!(point * 3, Point::new(3, 6));
+        assert_eq!(-point, Point::new(-1, -2));
+        point += Point::new(4, 4);
+        assert_eq!(point - Point::new(5, 6), Point::default());
+    }
+
+    #[test]
+    fn distances() {
+        let a = Point::new(1, -2);
+        let b = Point::new(-3, 5);
+        assert_eq!(a.manhattan(&b), 11);
+        assert_eq!(a.chebyshev(&b), 7);
+        assert_eq!(a.manhattan(&a), 0);
+    }
+
+    #[test]
+    fn rotate() {
+        let east = Direction::East.offset() * 2;
+        assert_eq!(east.rotate_right(), Direction::South.offset() * 2);
+        assert_eq!(east.rotate_left(), Direction::North.offset() * 2);
+        assert_eq!(Point::new(10, -4).rotate_right(), Point::new(4, 10));
+        assert_eq!(Point::new(10, -4).rotate_left().rotate_right(), Point::new(10, -4));
+    }
+
+    #[test]
+    fn turn() {
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::East.reverse(), Direction::West);
+        for direction in Direction::all().iter() {
+            assert_eq!(direction.turn_right().turn_left(), *direction);
+            assert_eq!(direction.offset().rotate_right(), direction.turn_right().offset());
+        }
+    }
+
+    #[test]
+    fn conversion() {
+        let pos = CartesianCoordinate::new(3, 4);
+        assert_eq!(Point::from(pos), Point::new(3, 4));
+        assert_eq!(CartesianCoordinate::try_from(Point::new(3, 4)).unwrap(), pos);
+        assert!(CartesianCoordinate::try_from(Point::new(-1, 4)).is_err());
+        assert!(CartesianCoordinate::try_from(Point::new(1, -4)).is_err());
+    }
+}
@@ -1,14 +1,14 @@
-use std::{str, marker, fmt, cmp};
+use std::{str, marker, fmt, cmp, clone, hash, default, ops};
 use crate::grid::coordinate::CartesianCoordinate;
 
-#[derive(fmt::Debug)]
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, hash::Hash, default::Default)]
 pub struct Grid<T> {
     internal: Vec<Vec<T>>,
     coordinates: Vec<CartesianCoordinate>
 }
 
 impl<T> Grid<T> {
-    fn new(vec2d: Vec<Vec<T>>) -> Self {
+    pub fn new(vec2d: Vec<Vec<T>>) -> Self {
         let mut coordinates: Vec<CartesianCoordinate> = Vec::new();
         for (y, row) in vec2d.iter().enumerate() {
             for (x, _element) in row.iter().enumerate() {
@@ -22,10 +22,37 @@ impl<T> Grid<T> {
     }
 }
 
-#[derive(fmt::Debug)]
-pub struct ParseGridError;
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub enum Neighbourhood {
+    /// Orthogonal neighbours only
+    Four,
+    /// Orthogonal and diagonal neighbours
+    Eight
+}
+
+/// Rows and columns are counted from 1
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq)]
+pub enum ParseGridError {
+    /// The character does not map to a cell
+    InvalidCell { row: usize, column: usize, found: char },
+    /// The row is not as long as the first one
+    RaggedRow { row: usize, expected: usize, found: usize },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGridError::InvalidCell{row, column, found} =>
+                write!(f, "invalid cell {:?} at row {}, column {}", found, row, column),
+            ParseGridError::RaggedRow{row, expected, found} =>
+                write!(f, "row {} has {} cells, expected {}", row, found, expected),
+        }
+    }
+}
 #[derive(fmt::Debug)]
 pub struct GridOutOfBoundsError;
+#[derive(fmt::Debug)]
+pub struct GridShapeError;
 
 impl<T: fmt::Display> fmt::Display for Grid<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -41,68 +68,119 @@ impl<T: fmt::Display> fmt::Display for Grid<T> {
 }
 
 impl<T: str::FromStr + fmt::Display> str::FromStr for Grid<T> {
-    type Err = ParseGridError;    
+    type Err = ParseGridError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut grid: Vec<Vec<T>> = Vec::new();
-        for line in s.lines() {
-            let mut row: Vec<T> = Vec::new(); 
-            for c in line.chars() {
-                row.push(c.to_string().parse()
-                    .or(Err(ParseGridError))?);
+        Grid::parse_with(s, |c| c.to_string().parse().ok())
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parses one cell per character with `cell`, which returns `None` for characters it
+    /// does not accept. All rows must be as long as the first.
+    pub fn parse_with<F>(s: &str, cell: F) -> Result<Self, ParseGridError>
+        where F: Fn(char) -> Option<T> {
+        Ok(Grid::new(parse_rows(s, cell, true)?))
+    }
+}
+
+impl<T: clone::Clone> Grid<T> {
+    /// Like `parse_with`, but pads short rows with `padding` up to the longest row
+    pub fn parse_padded_with<F>(s: &str, cell: F, padding: T) -> Result<Self, ParseGridError>
+        where F: Fn(char) -> Option<T> {
+        let mut rows = parse_rows(s, cell, false)?;
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        for row in rows.iter_mut() {
+            row.resize(width, padding.clone());
+        }
+        Ok(Grid::new(rows))
+    }
+    /// Parses with a table of which character stands for which cell
+    pub fn parse_with_table(s: &str, table: &[(char, T)]) -> Result<Self, ParseGridError> {
+        Grid::parse_with(s, |c| table.iter()
+            .find(|(symbol, _cell)| *symbol == c)
+            .map(|(_symbol, cell)| cell.clone()))
+    }
+}
+
+/// Reports the first error in reading order. With `same_width`, every row must be as long
+/// as the first.
+fn parse_rows<T, F>(s: &str, cell: F, same_width: bool) -> Result<Vec<Vec<T>>, ParseGridError>
+    where F: Fn(char) -> Option<T> {
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    for (y, line) in s.lines().enumerate() {
+        let row = line.chars()
+            .enumerate()
+            .map(|(x, c)| cell(c).ok_or(ParseGridError::InvalidCell{row: y + 1, column: x + 1, found: c}))
+            .collect::<Result<Vec<T>, ParseGridError>>()?;
+        if let Some(first) = rows.first() {
+            if same_width && row.len() != first.len() {
+                return Err(ParseGridError::RaggedRow{row: y + 1, expected: first.len(), found: row.len()});
             }
-            grid.push(row);
         }
-        Ok(Grid::new(grid))
+        rows.push(row);
     }
+    Ok(rows)
 }
 
-impl<T: marker::Copy + cmp::PartialEq> Grid<T> {
+impl<T: clone::Clone> Grid<T> {
     pub fn at(self: &Self, pos: &CartesianCoordinate) -> Result<T, GridOutOfBoundsError> {
-        let row = match self.internal.get(pos.y) {
-            Some(s) => s,
-            None => return Err(GridOutOfBoundsError)
-        };
-        let element = match row.get(pos.x) {
-            Some(s) => s,
-            None => return Err(GridOutOfBoundsError)
-        };
-        Ok(*element)
+        match self.get(pos) {
+            Some(element) => Ok(element.clone()),
+            None => Err(GridOutOfBoundsError)
+        }
     }
+}
+
+impl<T: cmp::PartialEq> Grid<T> {
     pub fn adjacent_to(self: &Self, src_pos: &CartesianCoordinate, target: T) -> usize {
-        let src_x = src_pos.x as isize;
-        let src_y = src_pos.y as isize;
-        vec![(src_x-1, src_y), (src_x+1, src_y), (src_x, src_y-1), (src_x, src_y+1), (src_x-1, src_y-1), (src_x+1, src_y-1), (src_x-1, src_y+1), (src_x+1, src_y+1)]
+        self.neighbours(src_pos, Neighbourhood::Eight)
             .iter()
-            .filter(|(x,y)| *x >= 0 && *y >= 0)
-            .map(|(x,y)| CartesianCoordinate::new(*x as usize,*y as usize))
-            .filter(|pos| {
-                match self.at(pos) {
-                    Ok(val) => val == target,
-                    Err(_e) => false,
-                }
-            })
+            .filter(|pos| self.get(pos) == Some(&target))
             .count()
     }
     pub fn number_of(self: &Self, element: T) -> usize {
-        self.coordinates.iter()
-            .filter_map(|pos| self.at(pos).ok())
-            .filter(|x| *x == element)
+        self.internal.iter()
+            .flat_map(|row| row.iter())
+            .filter(|x| **x == element)
             .count()
     }
 }
 
-impl<T: marker::Copy> Grid<T> {
+impl<T> Grid<T> {
+    pub fn get(&self, pos: &CartesianCoordinate) -> Option<&T> {
+        self.internal.get(pos.y).and_then(|row| row.get(pos.x))
+    }
+    pub fn get_mut(&mut self, pos: &CartesianCoordinate) -> Option<&mut T> {
+        self.internal.get_mut(pos.y).and_then(|row| row.get_mut(pos.x))
+    }
     pub fn set(self: &mut Self, pos: &CartesianCoordinate, new: T) -> Result<(), GridOutOfBoundsError> {
-        let row = match self.internal.get_mut(pos.y) {
-            Some(row) => row,
-            None => return Err(GridOutOfBoundsError), 
-        };
-        match row.get(pos.x) {
-            None => return Err(GridOutOfBoundsError),
-            _ => {},
-        };
-        row[pos.x] = new;
-        Ok(())
+        match self.get_mut(pos) {
+            Some(element) => {
+                *element = new;
+                Ok(())
+            },
+            None => Err(GridOutOfBoundsError)
+        }
+    }
+    /// Grid of the same shape with `f` applied to every cell
+    pub fn map<U, F: Fn(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid::new(self.internal.iter()
+            .map(|row| row.iter().map(&f).collect())
+            .collect())
+    }
+}
+
+/// Panics if `pos` is outside the grid
+impl<T> ops::Index<CartesianCoordinate> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos: CartesianCoordinate) -> &T {
+        &self.internal[pos.y][pos.x]
+    }
+}
+
+impl<T> ops::IndexMut<CartesianCoordinate> for Grid<T> {
+    fn index_mut(&mut self, pos: CartesianCoordinate) -> &mut T {
+        &mut self.internal[pos.y][pos.x]
     }
 }
 
@@ -123,6 +201,22 @@ impl<T> Grid<T> {
     pub fn coordinates(self: & Self) -> Vec<CartesianCoordinate> {
         self.coordinates.clone()
     }
+    pub fn rows(&self) -> &[Vec<T>] {
+        &self.internal
+    }
+    /// In-bounds neighbours, orthogonal ones first
+    pub fn neighbours(&self, pos: &CartesianCoordinate, neighbourhood: Neighbourhood) -> Vec<CartesianCoordinate> {
+        let offsets: &[(isize, isize)] = match neighbourhood {
+            Neighbourhood::Four => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+            Neighbourhood::Eight => &[(0, -1), (1, 0), (0, 1), (-1, 0), (1, -1), (1, 1), (-1, 1), (-1, -1)],
+        };
+        offsets.iter()
+            .map(|(dx, dy)| (pos.x as isize + dx, pos.y as isize + dy))
+            .filter(|(x, y)| *x >= 0 && *y >= 0)
+            .map(|(x, y)| CartesianCoordinate::new(x as usize, y as usize))
+            .filter(|pos| self.internal.get(pos.y).is_some_and(|row| pos.x < row.len()))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -135,6 +229,59 @@ mod tests {
         assert_eq!(grid.width().unwrap(), 5);
     }
 
+    #[test]
+    fn parse_errors() {
+        assert_eq!("ab\nc".parse::<Grid<char>>(), Err(ParseGridError::RaggedRow{row: 2, expected: 2, found: 1}));
+        assert_eq!("12\n3x".parse::<Grid<u8>>(), Err(ParseGridError::InvalidCell{row: 2, column: 2, found: 'x'}));
+        assert_eq!("12\n3\n4x".parse::<Grid<u8>>(), Err(ParseGridError::RaggedRow{row: 2, expected: 2, found: 1}));
+        assert_eq!(ParseGridError::InvalidCell{row: 2, column: 2, found: 'x'}.to_string(), "invalid cell 'x' at row 2, column 2");
+        assert_eq!(ParseGridError::RaggedRow{row: 2, expected: 2, found: 1}.to_string(), "row 2 has 1 cells, expected 2");
+    }
+
+    #[test]
+    fn parse_with() {
+        let grid = Grid::parse_with("#.\n.#", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        }).unwrap();
+        assert_eq!(grid, Grid::new(vec![vec![true, false], vec![false, true]]));
+        assert_eq!(Grid::parse_with("#?", |c| Some(c).filter(|c| *c == '#')), Err(ParseGridError::InvalidCell{row: 1, column: 2, found: '?'}));
+    }
+
+    #[test]
+    fn parse_with_table() {
+        let grid = Grid::parse_with_table("L.#", &[('L', 1), ('#', 2), ('.', 0)]).unwrap();
+        assert_eq!(grid, Grid::new(vec![vec![1, 0, 2]]));
+        assert!(Grid::parse_with_table("LX", &[('L', 1)]).is_err());
+    }
+
+    #[test]
+    fn parse_padded() {
+        let grid = Grid::parse_padded_with("a\nabc\n", Some, ' ').unwrap();
+        assert_eq!(grid.to_string(), "a  \nabc\n");
+        assert_eq!(grid.width().unwrap(), 3);
+    }
+
+    #[test]
+    fn non_copy_cells() {
+        let mut grid = Grid::new(vec![
+            vec![String::from("a"), String::from("b")],
+            vec![String::from("c"), String::from("a")],
+        ]);
+        assert_eq!(grid.get(&CartesianCoordinate::new(1, 0)), Some(&String::from("b")));
+        assert_eq!(grid.get(&CartesianCoordinate::new(2, 0)), None);
+        grid.get_mut(&CartesianCoordinate::new(0, 1)).unwrap().push('!');
+        grid[CartesianCoordinate::new(1, 0)].push('?');
+        assert_eq!(grid[CartesianCoordinate::new(0, 1)], "c!");
+        assert_eq!(grid.at(&CartesianCoordinate::new(1, 0)).unwrap(), "b?");
+        assert_eq!(grid.number_of(String::from("a")), 2);
+        assert_eq!(grid.adjacent_to(&CartesianCoordinate::new(1, 0), String::from("a")), 2);
+        grid.set(&CartesianCoordinate::new(1, 1), String::from("d")).unwrap();
+        assert!(grid.set(&CartesianCoordinate::new(0, 2), String::new()).is_err());
+        assert_eq!(grid.map(String::len), Grid::new(vec![vec![1, 2], vec![2, 1]]));
+    }
+
     #[test]
     fn height() {
         let grid: Grid<char> = "abcde\n".parse().unwrap();
@@ -182,6 +329,17 @@ mod tests {
         assert_eq!("###\n#.#\n###".parse::<Grid<char>>().unwrap().adjacent_to(&CartesianCoordinate::new(1,1), '#'), 8);
     }
 
+    #[test]
+    fn neighbours() {
+        let grid: Grid<char> = "abc\ndef\nghi".parse().unwrap();
+        assert_eq!(grid.neighbours(&CartesianCoordinate::new(0,0), Neighbourhood::Four), vec![
+            CartesianCoordinate::new(1,0), CartesianCoordinate::new(0,1)
+        ]);
+        assert_eq!(grid.neighbours(&CartesianCoordinate::new(1,1), Neighbourhood::Four).len(), 4);
+        assert_eq!(grid.neighbours(&CartesianCoordinate::new(1,1), Neighbourhood::Eight).len(), 8);
+        assert_eq!(grid.neighbours(&CartesianCoordinate::new(2,2), Neighbourhood::Eight).len(), 3);
+    }
+
     #[test]
     fn set() {
         let mut grid: Grid<char> = "###".parse().unwrap();
@@ -0,0 +1,262 @@
+use std::{fmt, cmp, clone, marker, hash, default, ops, convert, str};
+use std::collections::HashMap;
+use crate::grid::automaton::Cell;
+
+/// Axial coordinate on a hex grid with pointy-topped tiles. `q` grows to the east
+/// and `r` to the south-east.
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash, default::Default)]
+pub struct Axial {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Cube coordinate, always satisfying `x + y + z == 0`
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash, default::Default)]
+pub struct Cube {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone, marker::Copy, hash::Hash)]
+pub enum HexDirection {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast
+}
+
+#[derive(fmt::Debug)]
+pub struct ParseHexDirectionError;
+
+impl Axial {
+    pub fn new(q: i32, r: i32) -> Self {
+        Axial{
+            q, r
+        }
+    }
+    /// Where walking `path` from the origin ends up
+    pub fn from_path(path: &[HexDirection]) -> Self {
+        path.iter().fold(Axial::default(), |pos, direction| pos.neighbour(*direction))
+    }
+    pub fn neighbour(&self, direction: HexDirection) -> Axial {
+        *self + direction.offset()
+    }
+    pub fn neighbours(&self) -> [Axial; 6] {
+        let mut neighbours = [*self; 6];
+        for (neighbour, direction) in neighbours.iter_mut().zip(HexDirection::all().iter()) {
+            *neighbour = self.neighbour(*direction);
+        }
+        neighbours
+    }
+    /// Number of steps between the two tiles
+    pub fn distance(&self, other: &Axial) -> i32 {
+        let dq = self.q - other.q;
+        let dr = self.r - other.r;
+        (dq.abs() + dr.abs() + (dq + dr).abs()) / 2
+    }
+}
+
+impl ops::Add for Axial {
+    type Output = Axial;
+    fn add(self, other: Axial) -> Axial {
+        Axial::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl convert::From<Axial> for Cube {
+    fn from(pos: Axial) -> Self {
+        Cube{
+            x: pos.q,
+            y: -pos.q - pos.r,
+            z: pos.r,
+        }
+    }
+}
+
+impl convert::From<Cube> for Axial {
+    fn from(pos: Cube) -> Self {
+        Axial::new(pos.x, pos.z)
+    }
+}
+
+impl Cell for Axial {
+    fn for_each_neighbour<F: FnMut(Self, usize)>(&self, mut f: F) {
+        for neighbour in self.neighbours().iter() {
+            f(*neighbour, 1);
+        }
+    }
+}
+
+impl HexDirection {
+    pub fn all() -> [HexDirection; 6] {
+        [HexDirection::East, HexDirection::SouthEast, HexDirection::SouthWest,
+            HexDirection::West, HexDirection::NorthWest, HexDirection::NorthEast]
+    }
+    pub fn offset(&self) -> Axial {
+        match self {
+            HexDirection::East => Axial::new(1, 0),
+            HexDirection::SouthEast => Axial::new(0, 1),
+            HexDirection::SouthWest => Axial::new(-1, 1),
+            HexDirection::West => Axial::new(-1, 0),
+            HexDirection::NorthWest => Axial::new(0, -1),
+            HexDirection::NorthEast => Axial::new(1, -1),
+        }
+    }
+    /// Splits an undelimited string of directions such as `nwwswee`
+    pub fn parse_path(s: &str) -> Result<Vec<HexDirection>, ParseHexDirectionError> {
+        let mut path: Vec<HexDirection> = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let length = match rest.as_bytes()[0] {
+                b'n' | b's' => 2,
+                _ => 1,
+            };
+            path.push(rest.get(..length).ok_or(ParseHexDirectionError)?.parse()?);
+            rest = &rest[length..];
+        }
+        Ok(path)
+    }
+}
+
+impl str::FromStr for HexDirection {
+    type Err = ParseHexDirectionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "e" => Ok(HexDirection::East),
+            "se" => Ok(HexDirection::SouthEast),
+            "sw" => Ok(HexDirection::SouthWest),
+            "w" => Ok(HexDirection::West),
+            "nw" => Ok(HexDirection::NorthWest),
+            "ne" => Ok(HexDirection::NorthEast),
+            _ => Err(ParseHexDirectionError)
+        }
+    }
+}
+
+/// Unbounded hex grid that only stores occupied tiles. Every other tile reads as `default`.
+#[derive(fmt::Debug, clone::Clone)]
+pub struct HexGrid<T> {
+    tiles: HashMap<Axial, T>,
+    default: T,
+}
+
+impl<T> HexGrid<T> {
+    pub fn new(default: T) -> Self {
+        HexGrid{
+            tiles: HashMap::new(),
+            default,
+        }
+    }
+    pub fn get(&self, pos: &Axial) -> &T {
+        self.tiles.get(pos).unwrap_or(&self.default)
+    }
+    pub fn insert(&mut self, pos: Axial, value: T) -> Option<T> {
+        self.tiles.insert(pos, value)
+    }
+    pub fn remove(&mut self, pos: &Axial) -> Option<T> {
+        self.tiles.remove(pos)
+    }
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+    /// Occupied tiles, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&Axial, &T)> {
+        self.tiles.iter()
+    }
+    /// Occupied tiles whose value satisfies `predicate`, e.g. to seed an `Automaton`
+    pub fn positions_where<'a, F>(&'a self, predicate: F) -> impl Iterator<Item = Axial> + 'a
+        where F: Fn(&T) -> bool + 'a {
+        self.tiles.iter()
+            .filter(move |(_pos, value)| predicate(value))
+            .map(|(pos, _value)| *pos)
+    }
+}
+
+impl HexGrid<bool> {
+    /// Flips the tile and returns its new value
+    pub fn toggle(&mut self, pos: Axial) -> bool {
+        let flipped = !*self.get(&pos);
+        self.insert(pos, flipped);
+        flipped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::automaton::{Automaton, Rule};
+
+    #[test]
+    fn parse_path() {
+        assert_eq!(HexDirection::parse_path("esenee").unwrap(), vec![
+            HexDirection::East, HexDirection::SouthEast, HexDirection::NorthEast, HexDirection::East
+        ]);
+        assert_eq!(HexDirection::parse_path("nwwswee").unwrap().len(), 5);
+        assert!(HexDirection::parse_path("nx").is_err());
+        assert!(HexDirection::parse_path("ee n").is_err());
+        assert!(HexDirection::parse_path("s").is_err());
+    }
+
+    #[test]
+    fn from_path() {
+        assert_eq!(Axial::from_path(&HexDirection::parse_path("nwwswee").unwrap()), Axial::default());
+        assert_eq!(Axial::from_path(&HexDirection::parse_path("esew").unwrap()), Axial::default().neighbour(HexDirection::SouthEast));
+    }
+
+    #[test]
+    fn directions_cancel_out() {
+        let all = HexDirection::all();
+        assert_eq!(Axial::from_path(&all), Axial::default());
+        for (direction, opposite) in all.iter().zip(all.iter().skip(3)) {
+            assert_eq!(direction.offset() + opposite.offset(), Axial::default());
+        }
+    }
+
+    #[test]
+    fn cube_conversion() {
+        let pos = Axial::new(3, -5);
+        let cube = Cube::from(pos);
+        assert_eq!(cube.x + cube.y + cube.z, 0);
+        assert_eq!(Axial::from(cube), pos);
+    }
+
+    #[test]
+    fn distance() {
+        let origin = Axial::default();
+        assert_eq!(origin.distance(&origin), 0);
+        for neighbour in origin.neighbours().iter() {
+            assert_eq!(origin.distance(neighbour), 1);
+        }
+        let far = Axial::from_path(&HexDirection::parse_path("eene").unwrap());
+        assert_eq!(origin.distance(&far), 3);
+        assert_eq!(far.distance(&origin), 3);
+    }
+
+    #[test]
+    fn toggle() {
+        let mut grid = HexGrid::new(false);
+        let pos = Axial::from_path(&HexDirection::parse_path("nwwswee").unwrap());
+        assert!(grid.toggle(pos));
+        assert!(!grid.toggle(pos));
+        grid.toggle(Axial::new(1, 0));
+        assert_eq!(grid.positions_where(|black| *black).collect::<Vec<Axial>>(), vec![Axial::new(1, 0)]);
+    }
+
+    #[test]
+    fn automaton_on_hex_tiles() {
+        let mut grid = HexGrid::new(false);
+        grid.toggle(Axial::new(0, 0));
+        grid.toggle(Axial::new(1, 0));
+        let mut automaton = Automaton::new(grid.positions_where(|black| *black), Rule::new(&[2], &[1, 2]));
+        automaton.step();
+        assert_eq!(automaton.population(), 4);
+        assert!(automaton.is_active(&Axial::new(1, -1)));
+        assert!(automaton.is_active(&Axial::new(0, 1)));
+    }
+}
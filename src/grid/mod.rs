@@ -1,2 +1,15 @@
 pub mod grid;
 pub mod coordinate;
+pub mod transform;
+pub mod view;
+pub mod pattern;
+pub mod path;
+pub mod region;
+pub mod wrap;
+pub mod sparse;
+pub mod automaton;
+pub mod hex;
+pub mod bits;
+pub mod render;
+pub mod record;
+pub mod simulation;
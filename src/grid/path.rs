@@ -0,0 +1,236 @@
+use std::{fmt, cmp, clone};
+use std::collections::{BinaryHeap, VecDeque};
+use crate::grid::grid::{Grid, Neighbourhood};
+use crate::grid::coordinate::CartesianCoordinate;
+
+/// Steps include both the start and the goal
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq)]
+pub struct Path {
+    pub cost: u64,
+    pub steps: Vec<CartesianCoordinate>,
+}
+
+struct Search {
+    distances: Vec<Vec<Option<u64>>>,
+    previous: Vec<Vec<Option<CartesianCoordinate>>>,
+}
+
+impl Search {
+    fn new<T>(grid: &Grid<T>) -> Self {
+        Search{
+            distances: grid.rows().iter().map(|row| vec![None; row.len()]).collect(),
+            previous: grid.rows().iter().map(|row| vec![None; row.len()]).collect(),
+        }
+    }
+    fn distance(&self, pos: &CartesianCoordinate) -> Option<u64> {
+        self.distances[pos.y][pos.x]
+    }
+    fn path_to(&self, goal: &CartesianCoordinate) -> Option<Path> {
+        let cost = self.distance(goal)?;
+        let mut steps = vec![*goal];
+        while let Some(previous) = self.previous[steps[steps.len() - 1].y][steps[steps.len() - 1].x] {
+            steps.push(previous);
+        }
+        steps.reverse();
+        Some(Path{
+            cost,
+            steps,
+        })
+    }
+}
+
+fn in_bounds<T>(grid: &Grid<T>, pos: &CartesianCoordinate) -> bool {
+    grid.rows().get(pos.y).is_some_and(|row| pos.x < row.len())
+}
+
+/// Shortest path by number of steps. The start cell is never checked for passability.
+pub fn bfs<T, F>(grid: &Grid<T>, start: &CartesianCoordinate, goal: &CartesianCoordinate, neighbourhood: Neighbourhood, passable: F) -> Option<Path>
+    where F: Fn(&T) -> bool {
+    if !in_bounds(grid, start) || !in_bounds(grid, goal) {
+        return None;
+    }
+    let mut search = Search::new(grid);
+    let mut queue: VecDeque<CartesianCoordinate> = VecDeque::new();
+    search.distances[start.y][start.x] = Some(0);
+    queue.push_back(*start);
+    while let Some(pos) = queue.pop_front() {
+        if pos == *goal {
+            break;
+        }
+        let distance = search.distance(&pos).unwrap();
+        for next in grid.neighbours(&pos, neighbourhood) {
+            if search.distance(&next).is_some() || !passable(&grid.rows()[next.y][next.x]) {
+                continue;
+            }
+            search.distances[next.y][next.x] = Some(distance + 1);
+            search.previous[next.y][next.x] = Some(pos);
+            queue.push_back(next);
+        }
+    }
+    search.path_to(goal)
+}
+
+/// Cheapest path, where `cost` gives the price of entering a cell or `None` if it is impassable
+pub fn dijkstra<T, F>(grid: &Grid<T>, start: &CartesianCoordinate, goal: &CartesianCoordinate, neighbourhood: Neighbourhood, cost: F) -> Option<Path>
+    where F: Fn(&T) -> Option<u64> {
+    if !in_bounds(grid, start) || !in_bounds(grid, goal) {
+        return None;
+    }
+    search(grid, start, Some(goal), neighbourhood, cost, |_pos| 0)
+        .path_to(goal)
+}
+
+/// Like `dijkstra`, guided by the grid distance to the goal. Every step must cost at least 1.
+pub fn a_star<T, F>(grid: &Grid<T>, start: &CartesianCoordinate, goal: &CartesianCoordinate, neighbourhood: Neighbourhood, cost: F) -> Option<Path>
+    where F: Fn(&T) -> Option<u64> {
+    if !in_bounds(grid, start) || !in_bounds(grid, goal) {
+        return None;
+    }
+    let heuristic = |pos: &CartesianCoordinate| {
+        let dx = (pos.x as isize - goal.x as isize).unsigned_abs() as u64;
+        let dy = (pos.y as isize - goal.y as isize).unsigned_abs() as u64;
+        match neighbourhood {
+            Neighbourhood::Four => dx + dy,
+            Neighbourhood::Eight => cmp::max(dx, dy),
+        }
+    };
+    search(grid, start, Some(goal), neighbourhood, cost, heuristic)
+        .path_to(goal)
+}
+
+/// Cheapest cost from `source` to every reachable cell
+pub fn distances<T, F>(grid: &Grid<T>, source: &CartesianCoordinate, neighbourhood: Neighbourhood, cost: F) -> Grid<Option<u64>>
+    where F: Fn(&T) -> Option<u64> {
+    if !in_bounds(grid, source) {
+        return Grid::new(grid.rows().iter().map(|row| vec![None; row.len()]).collect());
+    }
+    Grid::new(search(grid, source, None, neighbourhood, cost, |_pos| 0).distances)
+}
+
+fn search<T, F, H>(grid: &Grid<T>, start: &CartesianCoordinate, goal: Option<&CartesianCoordinate>, neighbourhood: Neighbourhood, cost: F, heuristic: H) -> Search
+    where F: Fn(&T) -> Option<u64>, H: Fn(&CartesianCoordinate) -> u64 {
+    let mut search = Search::new(grid);
+    let mut queue: BinaryHeap<cmp::Reverse<(u64, u64, usize, usize)>> = BinaryHeap::new();
+    search.distances[start.y][start.x] = Some(0);
+    queue.push(cmp::Reverse((heuristic(start), 0, start.y, start.x)));
+    while let Some(cmp::Reverse((_estimate, distance, y, x))) = queue.pop() {
+        let pos = CartesianCoordinate::new(x, y);
+        if Some(&pos) == goal {
+            break;
+        }
+        if search.distance(&pos).is_some_and(|best| best < distance) {
+            continue;
+        }
+        for next in grid.neighbours(&pos, neighbourhood) {
+            let step = match cost(&grid.rows()[next.y][next.x]) {
+                Some(step) => step,
+                None => continue,
+            };
+            let next_distance = distance + step;
+            if search.distance(&next).is_some_and(|best| best <= next_distance) {
+                continue;
+            }
+            search.distances[next.y][next.x] = Some(next_distance);
+            search.previous[next.y][next.x] = Some(pos);
+            queue.push(cmp::Reverse((next_distance + heuristic(&next), next_distance, next.y, next.x)));
+        }
+    }
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "S..#....\n.#.#.##.\n.#...#..\n.####.#.\n......#G";
+
+    fn maze() -> Grid<char> {
+        MAZE.parse().unwrap()
+    }
+
+    fn open(c: &char) -> bool {
+        *c != '#'
+    }
+
+    fn cost(c: &char) -> Option<u64> {
+        match c {
+            '#' => None,
+            c => Some(c.to_digit(10).unwrap_or(1) as u64),
+        }
+    }
+
+    fn is_connected(path: &Path, neighbourhood: Neighbourhood, grid: &Grid<char>) -> bool {
+        path.steps.windows(2)
+            .all(|pair| grid.neighbours(&pair[0], neighbourhood).contains(&pair[1]))
+    }
+
+    #[test]
+    fn bfs_four() {
+        let grid = maze();
+        let path = bfs(&grid, &CartesianCoordinate::new(0, 0), &CartesianCoordinate::new(7, 4), Neighbourhood::Four, open).unwrap();
+        assert_eq!(path.cost, 15);
+        assert_eq!(path.steps.len(), 16);
+        assert_eq!(path.steps[0], CartesianCoordinate::new(0, 0));
+        assert_eq!(path.steps[15], CartesianCoordinate::new(7, 4));
+        assert!(is_connected(&path, Neighbourhood::Four, &grid));
+        assert!(path.steps.iter().all(|pos| grid.at(pos).unwrap() != '#'));
+    }
+
+    #[test]
+    fn bfs_eight() {
+        let grid = maze();
+        let path = bfs(&grid, &CartesianCoordinate::new(0, 0), &CartesianCoordinate::new(7, 4), Neighbourhood::Eight, open).unwrap();
+        assert_eq!(path.cost, 8);
+        assert!(is_connected(&path, Neighbourhood::Eight, &grid));
+    }
+
+    #[test]
+    fn bfs_unreachable() {
+        let grid: Grid<char> = "S#.\n#..\n..G".parse().unwrap();
+        assert!(bfs(&grid, &CartesianCoordinate::new(0, 0), &CartesianCoordinate::new(2, 2), Neighbourhood::Four, open).is_none());
+        assert!(bfs(&grid, &CartesianCoordinate::new(0, 0), &CartesianCoordinate::new(2, 2), Neighbourhood::Eight, open).is_some());
+    }
+
+    #[test]
+    fn dijkstra_weighted() {
+        let grid: Grid<char> = "1911\n1919\n1119".parse().unwrap();
+        let path = dijkstra(&grid, &CartesianCoordinate::new(0, 0), &CartesianCoordinate::new(3, 0), Neighbourhood::Four, cost).unwrap();
+        assert_eq!(path.cost, 7);
+        assert_eq!(path.steps, vec![
+            CartesianCoordinate::new(0, 0), CartesianCoordinate::new(0, 1), CartesianCoordinate::new(0, 2),
+            CartesianCoordinate::new(1, 2), CartesianCoordinate::new(2, 2), CartesianCoordinate::new(2, 1),
+            CartesianCoordinate::new(2, 0), CartesianCoordinate::new(3, 0),
+        ]);
+    }
+
+    #[test]
+    fn a_star_agrees_with_dijkstra() {
+        let grid: Grid<char> = "13#4\n2#12\n3115\n#921".parse().unwrap();
+        let start = CartesianCoordinate::new(0, 0);
+        for goal in grid.coordinates().iter().filter(|pos| grid.at(pos).unwrap() != '#') {
+            for neighbourhood in &[Neighbourhood::Four, Neighbourhood::Eight] {
+                let expected = dijkstra(&grid, &start, goal, *neighbourhood, cost).map(|path| path.cost);
+                let actual = a_star(&grid, &start, goal, *neighbourhood, cost).map(|path| path.cost);
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn all_distances() {
+        let grid: Grid<char> = "..#\n.#.\n...".parse().unwrap();
+        let distances = distances(&grid, &CartesianCoordinate::new(0, 0), Neighbourhood::Four, |c| if open(c) { Some(1) } else { None });
+        assert_eq!(distances, Grid::new(vec![
+            vec![Some(0), Some(1), None],
+            vec![Some(1), None, Some(5)],
+            vec![Some(2), Some(3), Some(4)],
+        ]));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let grid = maze();
+        assert!(bfs(&grid, &CartesianCoordinate::new(0, 0), &CartesianCoordinate::new(8, 0), Neighbourhood::Four, open).is_none());
+        assert!(a_star(&grid, &CartesianCoordinate::new(0, 9), &CartesianCoordinate::new(0, 0), Neighbourhood::Four, cost).is_none());
+    }
+}
@@ -0,0 +1,148 @@
+use std::{fmt, cmp, clone};
+use crate::grid::grid::Grid;
+use crate::grid::coordinate::CartesianCoordinate;
+
+/// Grid of cells to look for, where `None` matches any cell
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq)]
+pub struct Pattern<T> {
+    cells: Grid<Option<T>>,
+}
+
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq)]
+pub struct PatternMatch {
+    pub origin: CartesianCoordinate,
+    pub cells: Vec<CartesianCoordinate>,
+}
+
+impl<T> Pattern<T> {
+    pub fn new(cells: Grid<Option<T>>) -> Self {
+        Pattern{
+            cells
+        }
+    }
+}
+
+impl Pattern<char> {
+    /// Every `wildcard` character matches anything, and short lines are padded with wildcards
+    pub fn from_text(s: &str, wildcard: char) -> Self {
+        let cell = |c| Some(if c == wildcard { None } else { Some(c) });
+        Pattern::new(Grid::parse_padded_with(s, cell, None).unwrap())
+    }
+}
+
+impl<T: clone::Clone + cmp::PartialEq> Pattern<T> {
+    fn orientations(&self) -> Vec<Grid<Option<T>>> {
+        let mut orientations: Vec<Grid<Option<T>>> = Vec::new();
+        for orientation in self.cells.orientations() {
+            if !orientations.contains(&orientation) {
+                orientations.push(orientation);
+            }
+        }
+        orientations
+    }
+}
+
+impl<T: clone::Clone + cmp::PartialEq> Grid<T> {
+    /// Finds the pattern in all of its 8 orientations. Coordinates are in the grid's frame.
+    pub fn find_pattern(&self, pattern: &Pattern<T>) -> Vec<PatternMatch> {
+        let rows = self.rows();
+        let mut matches: Vec<PatternMatch> = Vec::new();
+        for orientation in pattern.orientations() {
+            let cells: Vec<(CartesianCoordinate, &T)> = orientation.rows().iter()
+                .enumerate()
+                .flat_map(|(y, row)| row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, cell)| cell.as_ref().map(|c| (CartesianCoordinate::new(x, y), c))))
+                .collect();
+            let pattern_height = orientation.height();
+            let pattern_width = orientation.width().unwrap_or(0);
+            if pattern_height > rows.len() {
+                continue;
+            }
+            for y in 0..=rows.len() - pattern_height {
+                let width = rows[y..y + pattern_height].iter().map(Vec::len).min().unwrap_or(0);
+                if pattern_width > width {
+                    continue;
+                }
+                for x in 0..=width - pattern_width {
+                    if cells.iter().all(|(pos, c)| rows[y + pos.y][x + pos.x] == **c) {
+                        matches.push(PatternMatch{
+                            origin: CartesianCoordinate::new(x, y),
+                            cells: cells.iter()
+                                .map(|(pos, _c)| CartesianCoordinate::new(x + pos.x, y + pos.y))
+                                .collect(),
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+}
+
+impl<T: clone::Clone + cmp::PartialEq> Grid<T> {
+    /// Sets every cell covered by a match to `replacement`, returning the matches
+    pub fn replace_pattern(&mut self, pattern: &Pattern<T>, replacement: T) -> Vec<PatternMatch> {
+        let matches = self.find_pattern(pattern);
+        for pos in matches.iter().flat_map(|m| m.cells.iter()) {
+            self[*pos] = replacement.clone();
+        }
+        matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_text() {
+        let pattern = Pattern::from_text(" #\n##\n#", ' ');
+        assert_eq!(pattern, Pattern::new(Grid::new(vec![
+            vec![None, Some('#')],
+            vec![Some('#'), Some('#')],
+            vec![Some('#'), None],
+        ])));
+    }
+
+    #[test]
+    fn find_pattern() {
+        let grid: Grid<char> = "#..\n##.\n...".parse().unwrap();
+        let matches = grid.find_pattern(&Pattern::from_text("#.\n##", ' '));
+        assert_eq!(matches, vec![PatternMatch{
+            origin: CartesianCoordinate::new(0, 0),
+            cells: vec![
+                CartesianCoordinate::new(0, 0), CartesianCoordinate::new(1, 0),
+                CartesianCoordinate::new(0, 1), CartesianCoordinate::new(1, 1),
+            ],
+        }]);
+    }
+
+    #[test]
+    fn find_pattern_in_other_orientations() {
+        let grid: Grid<char> = "...\n.##\n.#.".parse().unwrap();
+        let matches = grid.find_pattern(&Pattern::from_text("#\n##", ' '));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].origin, CartesianCoordinate::new(1, 1));
+    }
+
+    #[test]
+    fn symmetric_pattern_matches_once() {
+        let grid: Grid<char> = ".#.\n###\n.#.".parse().unwrap();
+        assert_eq!(grid.find_pattern(&Pattern::from_text(" # \n###\n # ", ' ')).len(), 1);
+    }
+
+    #[test]
+    fn pattern_larger_than_grid() {
+        let grid: Grid<char> = "##".parse().unwrap();
+        assert!(grid.find_pattern(&Pattern::from_text("###", ' ')).is_empty());
+    }
+
+    #[test]
+    fn replace_pattern() {
+        let mut grid: Grid<char> = "###.\n.#..".parse().unwrap();
+        let matches = grid.replace_pattern(&Pattern::from_text("###\n # ", ' '), 'O');
+        assert_eq!(matches.len(), 1);
+        assert_eq!(grid, "OOO.\n.O..".parse().unwrap());
+    }
+}
@@ -0,0 +1,103 @@
+use std::{fmt, clone, default, fs, io, path, thread, time};
+use crate::grid::grid::Grid;
+use crate::grid::render::Renderer;
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Keeps a copy of every generation a simulation passes through
+#[derive(fmt::Debug, clone::Clone)]
+pub struct Recorder<T> {
+    frames: Vec<Grid<T>>,
+}
+
+impl<T> Recorder<T> {
+    pub fn new() -> Self {
+        Recorder{
+            frames: Vec::new(),
+        }
+    }
+    pub fn frames(&self) -> &[Grid<T>] {
+        &self.frames
+    }
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+    /// Redraws every frame over the previous one. An `fps` of 0 plays without pausing.
+    pub fn play<W: io::Write>(&self, renderer: &Renderer<T>, writer: &mut W, fps: u32) -> io::Result<()> {
+        for (generation, frame) in self.frames.iter().enumerate() {
+            write!(writer, "{}Generation {}\n{}", CLEAR_SCREEN, generation, renderer.ansi(frame))?;
+            writer.flush()?;
+            if fps > 0 {
+                thread::sleep(time::Duration::from_secs(1) / fps);
+            }
+        }
+        Ok(())
+    }
+    /// Writes `frame-0000.ppm`, `frame-0001.ppm`, ... into `directory` and returns their paths
+    pub fn save_ppm_frames<P: AsRef<path::Path>>(&self, renderer: &Renderer<T>, directory: P, scale: usize) -> io::Result<Vec<path::PathBuf>> {
+        fs::create_dir_all(&directory)?;
+        let mut paths: Vec<path::PathBuf> = Vec::new();
+        for (generation, frame) in self.frames.iter().enumerate() {
+            let path = directory.as_ref().join(format!("frame-{:04}.ppm", generation));
+            renderer.save_ppm(frame, &path, scale)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+impl<T: clone::Clone> Recorder<T> {
+    pub fn record(&mut self, frame: &Grid<T>) {
+        self.frames.push(frame.clone());
+    }
+}
+
+impl<T> default::Default for Recorder<T> {
+    fn default() -> Self {
+        Recorder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::render::{Rgb, Style};
+
+    fn renderer() -> Renderer<char> {
+        Renderer::new(|c: &char| Style{
+            symbol: *c,
+            colour: Rgb(255, 255, 255),
+        })
+    }
+
+    fn recorder() -> Recorder<char> {
+        let mut recorder = Recorder::new();
+        recorder.record(&"#.".parse().unwrap());
+        recorder.record(&".#".parse().unwrap());
+        recorder
+    }
+
+    #[test]
+    fn play() {
+        let mut output: Vec<u8> = Vec::new();
+        recorder().play(&renderer(), &mut output, 0).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches(CLEAR_SCREEN).count(), 2);
+        assert!(output.contains("Generation 0\n\x1b[38;2;255;255;255m#.\x1b[0m\n"));
+        assert!(output.ends_with("Generation 1\n\x1b[38;2;255;255;255m.#\x1b[0m\n"));
+    }
+
+    #[test]
+    fn save_ppm_frames() {
+        let directory = std::env::temp_dir().join(format!("aoc-2020-frames-{}", std::process::id()));
+        let paths = recorder().save_ppm_frames(&renderer(), &directory, 3).unwrap();
+        assert_eq!(paths, vec![directory.join("frame-0000.ppm"), directory.join("frame-0001.ppm")]);
+        let image = fs::read(&paths[1]).unwrap();
+        assert!(image.starts_with(b"P6\n6 3\n255\n"));
+        assert_eq!(image.len(), b"P6\n6 3\n255\n".len() + 6 * 3 * 3);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+use std::{fmt, cmp, clone, marker};
+use std::collections::VecDeque;
+use crate::grid::grid::{Grid, Neighbourhood};
+use crate::grid::coordinate::CartesianCoordinate;
+
+/// Inclusive on both corners
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub struct BoundingBox {
+    pub min: CartesianCoordinate,
+    pub max: CartesianCoordinate,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> usize {
+        self.max.x - self.min.x + 1
+    }
+    pub fn height(&self) -> usize {
+        self.max.y - self.min.y + 1
+    }
+    fn include(&mut self, pos: &CartesianCoordinate) {
+        self.min = CartesianCoordinate::new(cmp::min(self.min.x, pos.x), cmp::min(self.min.y, pos.y));
+        self.max = CartesianCoordinate::new(cmp::max(self.max.x, pos.x), cmp::max(self.max.y, pos.y));
+    }
+}
+
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq)]
+pub struct Component {
+    pub label: usize,
+    pub size: usize,
+    pub bounds: BoundingBox,
+}
+
+/// Cells outside every component are labelled 0, components are labelled from 1
+/// in the order their first cell appears when scanning row by row
+#[derive(fmt::Debug, clone::Clone)]
+pub struct Components {
+    pub labels: Grid<usize>,
+    pub components: Vec<Component>,
+}
+
+impl<T> Grid<T> {
+    /// Every cell connected to `start` through cells satisfying `predicate`, in visiting order
+    pub fn flood_fill<F>(&self, start: &CartesianCoordinate, neighbourhood: Neighbourhood, predicate: F) -> Vec<CartesianCoordinate>
+        where F: Fn(&T) -> bool {
+        let mut visited: Vec<Vec<bool>> = self.rows().iter().map(|row| vec![false; row.len()]).collect();
+        fill(self, start, neighbourhood, &predicate, &mut visited)
+    }
+    pub fn label_components<F>(&self, neighbourhood: Neighbourhood, predicate: F) -> Components
+        where F: Fn(&T) -> bool {
+        let mut visited: Vec<Vec<bool>> = self.rows().iter().map(|row| vec![false; row.len()]).collect();
+        let mut labels: Vec<Vec<usize>> = self.rows().iter().map(|row| vec![0; row.len()]).collect();
+        let mut components: Vec<Component> = Vec::new();
+        for pos in self.coordinates() {
+            if visited[pos.y][pos.x] {
+                continue;
+            }
+            let cells = fill(self, &pos, neighbourhood, &predicate, &mut visited);
+            if cells.is_empty() {
+                continue;
+            }
+            let label = components.len() + 1;
+            let mut bounds = BoundingBox{
+                min: pos,
+                max: pos,
+            };
+            for cell in &cells {
+                labels[cell.y][cell.x] = label;
+                bounds.include(cell);
+            }
+            components.push(Component{
+                label,
+                size: cells.len(),
+                bounds,
+            });
+        }
+        Components{
+            labels: Grid::new(labels),
+            components,
+        }
+    }
+}
+
+fn fill<T, F>(grid: &Grid<T>, start: &CartesianCoordinate, neighbourhood: Neighbourhood, predicate: &F, visited: &mut [Vec<bool>]) -> Vec<CartesianCoordinate>
+    where F: Fn(&T) -> bool {
+    let rows = grid.rows();
+    let in_bounds = rows.get(start.y).is_some_and(|row| start.x < row.len());
+    if !in_bounds || visited[start.y][start.x] || !predicate(&rows[start.y][start.x]) {
+        return Vec::new();
+    }
+    let mut cells: Vec<CartesianCoordinate> = Vec::new();
+    let mut queue: VecDeque<CartesianCoordinate> = VecDeque::new();
+    visited[start.y][start.x] = true;
+    queue.push_back(*start);
+    while let Some(pos) = queue.pop_front() {
+        for next in grid.neighbours(&pos, neighbourhood) {
+            if !visited[next.y][next.x] && predicate(&rows[next.y][next.x]) {
+                visited[next.y][next.x] = true;
+                queue.push_back(next);
+            }
+        }
+        cells.push(pos);
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn flood_fill() {
+        let grid: Grid<char> = "##.\n.#.\n#.#".parse().unwrap();
+        let filled = grid.flood_fill(&CartesianCoordinate::new(0, 0), Neighbourhood::Four, |c| *c == '#');
+        assert_eq!(filled, vec![
+            CartesianCoordinate::new(0, 0), CartesianCoordinate::new(1, 0), CartesianCoordinate::new(1, 1),
+        ]);
+        let filled = grid.flood_fill(&CartesianCoordinate::new(0, 0), Neighbourhood::Eight, |c| *c == '#');
+        assert_eq!(filled.len(), 5);
+    }
+
+    #[test]
+    fn flood_fill_from_unmatched_cell() {
+        let grid: Grid<char> = "#.".parse().unwrap();
+        assert!(grid.flood_fill(&CartesianCoordinate::new(1, 0), Neighbourhood::Four, |c| *c == '#').is_empty());
+        assert!(grid.flood_fill(&CartesianCoordinate::new(2, 0), Neighbourhood::Four, |c| *c == '#').is_empty());
+    }
+
+    #[test]
+    fn label_components() {
+        let grid: Grid<char> = "##..#\n#...#\n..#..".parse().unwrap();
+        let components = grid.label_components(Neighbourhood::Four, |c| *c == '#');
+        assert_eq!(components.labels, Grid::new(vec![
+            vec![1, 1, 0, 0, 2],
+            vec![1, 0, 0, 0, 2],
+            vec![0, 0, 3, 0, 0],
+        ]));
+        assert_eq!(components.components, vec![
+            Component{
+                label: 1,
+                size: 3,
+                bounds: BoundingBox{ min: CartesianCoordinate::new(0, 0), max: CartesianCoordinate::new(1, 1) },
+            },
+            Component{
+                label: 2,
+                size: 2,
+                bounds: BoundingBox{ min: CartesianCoordinate::new(4, 0), max: CartesianCoordinate::new(4, 1) },
+            },
+            Component{
+                label: 3,
+                size: 1,
+                bounds: BoundingBox{ min: CartesianCoordinate::new(2, 2), max: CartesianCoordinate::new(2, 2) },
+            },
+        ]);
+    }
+
+    #[test]
+    fn label_components_eight() {
+        let grid: Grid<char> = "#...\n.#..\n..#.\n...#".parse().unwrap();
+        let components = grid.label_components(Neighbourhood::Eight, |c| *c == '#');
+        assert_eq!(components.components.len(), 1);
+        assert_eq!(components.components[0].bounds.width(), 4);
+        assert_eq!(components.components[0].bounds.height(), 4);
+        assert_eq!(grid.label_components(Neighbourhood::Four, |c| *c == '#').components.len(), 4);
+    }
+
+    #[test]
+    fn label_seat_layout() {
+        let grid: Grid<char> = fs::read_to_string("src/solutions/day11.test-input").unwrap().parse().unwrap();
+        let components = grid.label_components(Neighbourhood::Four, |c| *c == '.');
+        assert_eq!(components.components.iter().map(|c| c.size).sum::<usize>(), grid.number_of('.'));
+        for pos in grid.coordinates() {
+            let label = components.labels.at(&pos).unwrap();
+            assert_eq!(label == 0, grid.at(&pos).unwrap() != '.');
+            for neighbour in grid.neighbours(&pos, Neighbourhood::Four) {
+                if label != 0 && grid.at(&neighbour).unwrap() == '.' {
+                    assert_eq!(components.labels.at(&neighbour).unwrap(), label);
+                }
+            }
+        }
+        assert_eq!(grid.label_components(Neighbourhood::Eight, |c| *c == 'L').components.len(), 1);
+    }
+}
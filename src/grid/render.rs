@@ -0,0 +1,185 @@
+use std::{fmt, cmp, clone, marker, fs, io, path};
+use std::io::Write;
+use crate::grid::grid::Grid;
+use crate::grid::coordinate::CartesianCoordinate;
+
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// Perceived brightness, used for greyscale output
+    pub fn luminance(&self) -> u8 {
+        ((299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32) / 1000) as u8
+    }
+}
+
+/// How a cell is drawn: `symbol` is only used in the terminal, `colour` everywhere
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub struct Style {
+    pub symbol: char,
+    pub colour: Rgb,
+}
+
+/// Part of the grid to draw. Anything outside the grid is left out.
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub struct Viewport {
+    pub origin: CartesianCoordinate,
+    pub width: usize,
+    pub height: usize,
+}
+
+pub struct Renderer<T> {
+    palette: Box<dyn Fn(&T) -> Style>,
+    viewport: Option<Viewport>,
+}
+
+impl<T> Renderer<T> {
+    pub fn new<F>(palette: F) -> Self
+        where F: Fn(&T) -> Style + 'static {
+        Renderer{
+            palette: Box::new(palette),
+            viewport: None,
+        }
+    }
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        self.viewport = viewport;
+    }
+    fn styled_rows(&self, grid: &Grid<T>) -> Vec<Vec<Style>> {
+        let rows = grid.rows();
+        let viewport = self.viewport.unwrap_or(Viewport{
+            origin: CartesianCoordinate::new(0, 0),
+            width: rows.iter().map(Vec::len).max().unwrap_or(0),
+            height: rows.len(),
+        });
+        rows.iter()
+            .skip(viewport.origin.y)
+            .take(viewport.height)
+            .map(|row| row.iter()
+                .skip(viewport.origin.x)
+                .take(viewport.width)
+                .map(|cell| (self.palette)(cell))
+                .collect())
+            .collect()
+    }
+    /// Text with 24-bit ANSI colours, one line per row
+    pub fn ansi(&self, grid: &Grid<T>) -> String {
+        let mut output = String::new();
+        for row in self.styled_rows(grid) {
+            let mut current: Option<Rgb> = None;
+            for style in row {
+                if current != Some(style.colour) {
+                    let Rgb(r, g, b) = style.colour;
+                    output.push_str(&format!("\x1b[38;2;{};{};{}m", r, g, b));
+                    current = Some(style.colour);
+                }
+                output.push(style.symbol);
+            }
+            output.push_str("\x1b[0m\n");
+        }
+        output
+    }
+    fn write_image<W: io::Write>(&self, grid: &Grid<T>, writer: &mut W, scale: usize, greyscale: bool) -> io::Result<()> {
+        let rows = self.styled_rows(grid);
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let magic = if greyscale { "P5" } else { "P6" };
+        write!(writer, "{}\n{} {}\n255\n", magic, width * scale, rows.len() * scale)?;
+        let background = Rgb(0, 0, 0);
+        for row in rows {
+            let mut line: Vec<u8> = Vec::new();
+            for x in 0..width {
+                let colour = row.get(x).map_or(background, |style| style.colour);
+                for _i in 0..scale {
+                    match greyscale {
+                        true => line.push(colour.luminance()),
+                        false => line.extend_from_slice(&[colour.0, colour.1, colour.2]),
+                    }
+                }
+            }
+            for _i in 0..scale {
+                writer.write_all(&line)?;
+            }
+        }
+        Ok(())
+    }
+    /// Binary PPM (P6), every cell drawn as a `scale` by `scale` square
+    pub fn write_ppm<W: io::Write>(&self, grid: &Grid<T>, writer: &mut W, scale: usize) -> io::Result<()> {
+        self.write_image(grid, writer, scale, false)
+    }
+    /// Binary PGM (P5) using the luminance of each colour
+    pub fn write_pgm<W: io::Write>(&self, grid: &Grid<T>, writer: &mut W, scale: usize) -> io::Result<()> {
+        self.write_image(grid, writer, scale, true)
+    }
+    pub fn save_ppm<P: AsRef<path::Path>>(&self, grid: &Grid<T>, path: P, scale: usize) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+        self.write_ppm(grid, &mut writer, scale)?;
+        writer.flush()
+    }
+    pub fn save_pgm<P: AsRef<path::Path>>(&self, grid: &Grid<T>, path: P, scale: usize) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(fs::File::create(path)?);
+        self.write_pgm(grid, &mut writer, scale)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: Rgb = Rgb(255, 255, 255);
+    const RED: Rgb = Rgb(255, 0, 0);
+
+    fn renderer() -> Renderer<char> {
+        Renderer::new(|c: &char| Style{
+            symbol: *c,
+            colour: if *c == '#' { RED } else { WHITE },
+        })
+    }
+
+    #[test]
+    fn ansi() {
+        let grid: Grid<char> = "##.\n.#.".parse().unwrap();
+        assert_eq!(renderer().ansi(&grid), concat!(
+            "\x1b[38;2;255;0;0m##\x1b[38;2;255;255;255m.\x1b[0m\n",
+            "\x1b[38;2;255;255;255m.\x1b[38;2;255;0;0m#\x1b[38;2;255;255;255m.\x1b[0m\n",
+        ));
+    }
+
+    #[test]
+    fn ansi_viewport() {
+        let grid: Grid<char> = "abcd\nefgh\nijkl".parse().unwrap();
+        let mut renderer = renderer();
+        renderer.set_viewport(Some(Viewport{
+            origin: CartesianCoordinate::new(2, 1),
+            width: 5,
+            height: 1,
+        }));
+        assert_eq!(renderer.ansi(&grid), "\x1b[38;2;255;255;255mgh\x1b[0m\n");
+    }
+
+    #[test]
+    fn ppm() {
+        let grid: Grid<char> = "#.".parse().unwrap();
+        let mut image: Vec<u8> = Vec::new();
+        renderer().write_ppm(&grid, &mut image, 2).unwrap();
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _row in 0..2 {
+            expected.extend_from_slice(&[255, 0, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255]);
+        }
+        assert_eq!(image, expected);
+    }
+
+    #[test]
+    fn pgm() {
+        let grid: Grid<char> = "#\n.".parse().unwrap();
+        let mut image: Vec<u8> = Vec::new();
+        renderer().write_pgm(&grid, &mut image, 1).unwrap();
+        assert_eq!(image, [b"P5\n1 2\n255\n".to_vec(), vec![RED.luminance(), 255]].concat());
+    }
+
+    #[test]
+    fn luminance() {
+        assert_eq!(WHITE.luminance(), 255);
+        assert_eq!(Rgb(0, 0, 0).luminance(), 0);
+        assert_eq!(RED.luminance(), 76);
+    }
+}
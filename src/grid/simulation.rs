@@ -0,0 +1,182 @@
+use std::{fmt, cmp, clone, marker};
+use std::collections::HashMap;
+use std::hash::Hash;
+use crate::grid::grid::Grid;
+use crate::grid::coordinate::CartesianCoordinate;
+use crate::grid::record::Recorder;
+
+/// What one generation changed. A birth is a cell that became alive, a death one that stopped being alive.
+#[derive(fmt::Debug, clone::Clone, cmp::PartialEq, cmp::Eq)]
+pub struct GenerationDiff {
+    pub generation: usize,
+    pub changed: Vec<CartesianCoordinate>,
+    pub births: usize,
+    pub deaths: usize,
+}
+
+/// The simulation reached the same state at generation `start` and at `start + period`,
+/// so it repeats from there on. A period of 1 means it settled.
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub period: usize,
+}
+
+impl Cycle {
+    pub fn is_stable(&self) -> bool {
+        self.period == 1
+    }
+}
+
+/// Computes the next value of the cell at the given position
+type Update<T> = Box<dyn Fn(&Grid<T>, &CartesianCoordinate) -> T>;
+
+/// Steps a grid where every cell's next value is computed from the current generation
+pub struct Simulation<T> {
+    grid: Grid<T>,
+    rule: Update<T>,
+    is_alive: Box<dyn Fn(&T) -> bool>,
+    generation: usize,
+    history: Vec<GenerationDiff>,
+    /// Every state so far and the generation it was first reached in
+    seen: HashMap<Grid<T>, usize>,
+}
+
+impl<T: clone::Clone + cmp::Eq + Hash> Simulation<T> {
+    pub fn new<R, A>(grid: Grid<T>, rule: R, is_alive: A) -> Self
+        where R: Fn(&Grid<T>, &CartesianCoordinate) -> T + 'static,
+              A: Fn(&T) -> bool + 'static {
+        let mut seen: HashMap<Grid<T>, usize> = HashMap::new();
+        seen.insert(grid.clone(), 0);
+        Simulation{
+            grid,
+            rule: Box::new(rule),
+            is_alive: Box::new(is_alive),
+            generation: 0,
+            history: Vec::new(),
+            seen,
+        }
+    }
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+    /// Diffs of every generation stepped so far, oldest first
+    pub fn history(&self) -> &[GenerationDiff] {
+        &self.history
+    }
+    pub fn step(&mut self) -> &GenerationDiff {
+        let mut diff = GenerationDiff{
+            generation: self.generation + 1,
+            changed: Vec::new(),
+            births: 0,
+            deaths: 0,
+        };
+        let mut next: Vec<Vec<T>> = Vec::new();
+        for (y, row) in self.grid.rows().iter().enumerate() {
+            let mut next_row: Vec<T> = Vec::new();
+            for (x, cell) in row.iter().enumerate() {
+                let pos = CartesianCoordinate::new(x, y);
+                let value = (self.rule)(&self.grid, &pos);
+                if value != *cell {
+                    match ((self.is_alive)(cell), (self.is_alive)(&value)) {
+                        (false, true) => diff.births += 1,
+                        (true, false) => diff.deaths += 1,
+                        _ => {}
+                    }
+                    diff.changed.push(pos);
+                }
+                next_row.push(value);
+            }
+            next.push(next_row);
+        }
+        self.grid = Grid::new(next);
+        self.generation += 1;
+        self.history.push(diff);
+        self.history.last().unwrap()
+    }
+    /// Steps until a state comes round again, recording every generation from the current
+    /// one up to, but not including, the repeat. Every state is kept, so a repeat is only
+    /// reported for equal grids.
+    pub fn run(&mut self, mut recorder: Option<&mut Recorder<T>>) -> Cycle {
+        if let Some(recorder) = recorder.as_deref_mut() {
+            recorder.record(&self.grid);
+        }
+        loop {
+            self.step();
+            if let Some(start) = self.seen.insert(self.grid.clone(), self.generation) {
+                return Cycle{
+                    start,
+                    period: self.generation - start,
+                };
+            }
+            if let Some(recorder) = recorder.as_deref_mut() {
+                recorder.record(&self.grid);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::grid::Neighbourhood;
+
+    fn life(grid: &Grid<char>, pos: &CartesianCoordinate) -> char {
+        let alive = grid.at(pos).unwrap() == '#';
+        let neighbours = grid.neighbours(pos, Neighbourhood::Eight).iter()
+            .filter(|n| grid.at(n).unwrap() == '#')
+            .count();
+        match (alive, neighbours) {
+            (true, 2) | (_, 3) => '#',
+            _ => '.',
+        }
+    }
+
+    fn simulation(input: &str) -> Simulation<char> {
+        Simulation::new(input.parse().unwrap(), life, |c| *c == '#')
+    }
+
+    #[test]
+    fn diff() {
+        let mut blinker = simulation(".....\n.....\n.###.\n.....\n.....");
+        let diff = blinker.step().clone();
+        assert_eq!(diff.generation, 1);
+        assert_eq!(diff.births, 2);
+        assert_eq!(diff.deaths, 2);
+        assert_eq!(diff.changed, vec![
+            CartesianCoordinate::new(2, 1), CartesianCoordinate::new(1, 2),
+            CartesianCoordinate::new(3, 2), CartesianCoordinate::new(2, 3),
+        ]);
+        assert_eq!(blinker.history(), &[diff]);
+    }
+
+    #[test]
+    fn oscillator() {
+        let mut blinker = simulation(".....\n.....\n.###.\n.....\n.....");
+        assert_eq!(blinker.run(None), Cycle{start: 0, period: 2});
+        assert_eq!(blinker.generation(), 2);
+    }
+
+    #[test]
+    fn settles() {
+        let mut recorder = Recorder::new();
+        let mut dying = simulation("#..\n.#.\n...");
+        let cycle = dying.run(Some(&mut recorder));
+        assert_eq!(cycle, Cycle{start: 1, period: 1});
+        assert!(cycle.is_stable());
+        assert_eq!(dying.grid().number_of('#'), 0);
+        assert_eq!(recorder.len(), 2);
+        assert_eq!(dying.history()[1].changed, vec![]);
+    }
+
+    #[test]
+    fn cycle_after_transient() {
+        // Three in an L become a block, which never changes
+        let mut block = simulation("....\n.##.\n..#.\n....");
+        assert_eq!(block.run(None), Cycle{start: 1, period: 1});
+        assert_eq!(block.grid().number_of('#'), 4);
+    }
+}
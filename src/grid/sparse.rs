@@ -0,0 +1,173 @@
+use std::{fmt, clone, cmp};
+use std::collections::HashMap;
+use crate::grid::grid::Grid;
+use crate::grid::coordinate::Point;
+
+/// Unbounded grid that only stores occupied cells. Every other cell reads as `default`.
+#[derive(fmt::Debug, clone::Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+    default: T,
+    bounds: Option<(Point, Point)>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        SparseGrid{
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+    pub fn get(&self, pos: &Point) -> &T {
+        self.cells.get(pos).unwrap_or(&self.default)
+    }
+    pub fn is_occupied(&self, pos: &Point) -> bool {
+        self.cells.contains_key(pos)
+    }
+    pub fn insert(&mut self, pos: Point, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Point::new(cmp::min(min.x, pos.x), cmp::min(min.y, pos.y)),
+                Point::new(cmp::max(max.x, pos.x), cmp::max(max.y, pos.y)),
+            ),
+            None => (pos, pos),
+        });
+        self.cells.insert(pos, value)
+    }
+    pub fn remove(&mut self, pos: &Point) -> Option<T> {
+        let removed = self.cells.remove(pos)?;
+        if let Some((min, max)) = self.bounds {
+            if pos.x == min.x || pos.x == max.x || pos.y == min.y || pos.y == max.y {
+                self.bounds = self.calculate_bounds();
+            }
+        }
+        Some(removed)
+    }
+    fn calculate_bounds(&self) -> Option<(Point, Point)> {
+        let first = *self.cells.keys().next()?;
+        Some(self.cells.keys().fold((first, first), |(min, max), pos| (
+            Point::new(cmp::min(min.x, pos.x), cmp::min(min.y, pos.y)),
+            Point::new(cmp::max(max.x, pos.x), cmp::max(max.y, pos.y)),
+        )))
+    }
+    /// Smallest and largest corner of the box around every occupied cell, inclusive
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.bounds
+    }
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+    /// Occupied cells, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&Point, &T)> {
+        self.cells.iter()
+    }
+}
+
+impl<T: clone::Clone> SparseGrid<T> {
+    /// Dense copy of the bounding box, along with the position of its top left corner
+    pub fn to_grid(&self) -> (Grid<T>, Point) {
+        let (min, max) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return (Grid::new(Vec::new()), Point::default()),
+        };
+        let rows = (min.y..=max.y)
+            .map(|y| (min.x..=max.x)
+                .map(|x| self.get(&Point::new(x, y)).clone())
+                .collect())
+            .collect();
+        (Grid::new(rows), min)
+    }
+}
+
+impl<T: clone::Clone + cmp::PartialEq> SparseGrid<T> {
+    /// Occupies every cell that differs from `default`, placing the grid's top left corner at `origin`
+    pub fn from_grid(grid: &Grid<T>, origin: Point, default: T) -> Self {
+        let mut sparse = SparseGrid::new(default);
+        for (y, row) in grid.rows().iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if *cell != sparse.default {
+                    sparse.insert(origin + Point::new(x as i64, y as i64), cell.clone());
+                }
+            }
+        }
+        sparse
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_and_insert() {
+        let mut grid = SparseGrid::new('.');
+        assert_eq!(*grid.get(&Point::new(-5, 7)), '.');
+        assert_eq!(grid.insert(Point::new(-5, 7), '#'), None);
+        assert_eq!(grid.insert(Point::new(-5, 7), '@'), Some('#'));
+        assert_eq!(*grid.get(&Point::new(-5, 7)), '@');
+        assert!(grid.is_occupied(&Point::new(-5, 7)));
+        assert_eq!(grid.len(), 1);
+    }
+
+    #[test]
+    fn bounds_grow_on_insert() {
+        let mut grid = SparseGrid::new(0);
+        assert_eq!(grid.bounds(), None);
+        grid.insert(Point::new(2, 3), 1);
+        assert_eq!(grid.bounds(), Some((Point::new(2, 3), Point::new(2, 3))));
+        grid.insert(Point::new(-4, 5), 1);
+        grid.insert(Point::new(0, -1), 1);
+        assert_eq!(grid.bounds(), Some((Point::new(-4, -1), Point::new(2, 5))));
+    }
+
+    #[test]
+    fn bounds_shrink_on_remove() {
+        let mut grid = SparseGrid::new(0);
+        grid.insert(Point::new(0, 0), 1);
+        grid.insert(Point::new(1, 1), 1);
+        grid.insert(Point::new(10, -10), 1);
+        assert_eq!(grid.remove(&Point::new(1, 1)), Some(1));
+        assert_eq!(grid.bounds(), Some((Point::new(0, -10), Point::new(10, 0))));
+        grid.remove(&Point::new(10, -10));
+        assert_eq!(grid.bounds(), Some((Point::new(0, 0), Point::new(0, 0))));
+        assert_eq!(grid.remove(&Point::new(10, -10)), None);
+        grid.remove(&Point::new(0, 0));
+        assert_eq!(grid.bounds(), None);
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn iter() {
+        let mut grid = SparseGrid::new('.');
+        grid.insert(Point::new(1, 2), 'a');
+        grid.insert(Point::new(-1, 0), 'b');
+        let mut cells: Vec<(Point, char)> = grid.iter().map(|(pos, c)| (*pos, *c)).collect();
+        cells.sort_by_key(|(pos, _c)| (pos.y, pos.x));
+        assert_eq!(cells, vec![(Point::new(-1, 0), 'b'), (Point::new(1, 2), 'a')]);
+    }
+
+    #[test]
+    fn to_grid() {
+        let mut grid = SparseGrid::new('.');
+        grid.insert(Point::new(-1, -1), '#');
+        grid.insert(Point::new(1, 0), '#');
+        let (dense, origin) = grid.to_grid();
+        assert_eq!(origin, Point::new(-1, -1));
+        assert_eq!(dense, "#..\n..#".parse().unwrap());
+    }
+
+    #[test]
+    fn from_grid() {
+        let dense: Grid<char> = "#..\n.##".parse().unwrap();
+        let grid = SparseGrid::from_grid(&dense, Point::new(5, -2), '.');
+        assert_eq!(grid.len(), 3);
+        assert_eq!(*grid.get(&Point::new(5, -2)), '#');
+        assert_eq!(*grid.get(&Point::new(6, -1)), '#');
+        assert_eq!(*grid.get(&Point::new(6, -2)), '.');
+        assert_eq!(grid.to_grid(), (dense, Point::new(5, -2)));
+    }
+}
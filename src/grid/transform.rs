@@ -0,0 +1,129 @@
+use std::clone;
+use crate::grid::grid::Grid;
+
+impl<T: clone::Clone> Grid<T> {
+    /// Clockwise. Panics unless the grid is rectangular, as do `rotate_270` and `transpose`.
+    pub fn rotate_90(&self) -> Grid<T> {
+        let rows = self.rows();
+        let width = rectangular_width(rows);
+        Grid::new((0..width)
+            .map(|x| rows.iter().rev().map(|row| row[x].clone()).collect())
+            .collect())
+    }
+    pub fn rotate_180(&self) -> Grid<T> {
+        Grid::new(self.rows().iter()
+            .rev()
+            .map(|row| row.iter().rev().cloned().collect())
+            .collect())
+    }
+    /// Counter-clockwise, i.e. 270 degrees clockwise
+    pub fn rotate_270(&self) -> Grid<T> {
+        let rows = self.rows();
+        let width = rectangular_width(rows);
+        Grid::new((0..width).rev()
+            .map(|x| rows.iter().map(|row| row[x].clone()).collect())
+            .collect())
+    }
+    /// Mirrors left to right
+    pub fn flip_horizontal(&self) -> Grid<T> {
+        Grid::new(self.rows().iter()
+            .map(|row| row.iter().rev().cloned().collect())
+            .collect())
+    }
+    /// Mirrors top to bottom
+    pub fn flip_vertical(&self) -> Grid<T> {
+        Grid::new(self.rows().iter()
+            .rev()
+            .cloned()
+            .collect())
+    }
+    pub fn transpose(&self) -> Grid<T> {
+        let rows = self.rows();
+        let width = rectangular_width(rows);
+        Grid::new((0..width)
+            .map(|x| rows.iter().map(|row| row[x].clone()).collect())
+            .collect())
+    }
+    /// All 8 rotations and reflections, starting with the grid itself.
+    /// Symmetric grids yield some orientations more than once.
+    pub fn orientations(&self) -> impl Iterator<Item = Grid<T>> {
+        let flipped = self.flip_horizontal();
+        vec![
+            self.clone(), self.rotate_90(), self.rotate_180(), self.rotate_270(),
+            flipped.rotate_90(), flipped.rotate_180(), flipped.rotate_270(), flipped,
+        ].into_iter()
+    }
+}
+
+fn rectangular_width<T>(rows: &[Vec<T>]) -> usize {
+    let width = rows.first().map_or(0, Vec::len);
+    assert!(rows.iter().all(|row| row.len() == width), "Only rectangular grids can be rotated or transposed");
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> Grid<char> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn rotate_90() {
+        assert_eq!(grid("ab\ncd").rotate_90(), grid("ca\ndb"));
+        assert_eq!(grid("abc\ndef").rotate_90(), grid("da\neb\nfc"));
+    }
+
+    #[test]
+    fn rotate_180() {
+        assert_eq!(grid("abc\ndef").rotate_180(), grid("fed\ncba"));
+    }
+
+    #[test]
+    fn rotate_270() {
+        assert_eq!(grid("abc\ndef").rotate_270(), grid("cf\nbe\nad"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only rectangular grids can be rotated or transposed")]
+    fn ragged() {
+        Grid::new(vec![vec!['a', 'b'], vec!['c']]).transpose();
+    }
+
+    #[test]
+    fn rotations_compose() {
+        let original = grid("abc\ndef\nghi");
+        assert_eq!(original.rotate_90().rotate_90(), original.rotate_180());
+        assert_eq!(original.rotate_90().rotate_180(), original.rotate_270());
+        assert_eq!(original.rotate_270().rotate_90(), original);
+    }
+
+    #[test]
+    fn flip_horizontal() {
+        assert_eq!(grid("abc\ndef").flip_horizontal(), grid("cba\nfed"));
+    }
+
+    #[test]
+    fn flip_vertical() {
+        assert_eq!(grid("abc\ndef").flip_vertical(), grid("def\nabc"));
+    }
+
+    #[test]
+    fn transpose() {
+        assert_eq!(grid("abc\ndef").transpose(), grid("ad\nbe\ncf"));
+    }
+
+    #[test]
+    fn orientations_are_distinct_for_asymmetric_grid() {
+        let orientations: Vec<Grid<char>> = grid("ab\ncd").orientations().collect();
+        assert_eq!(orientations.len(), 8);
+        for (i, a) in orientations.iter().enumerate() {
+            for b in orientations.iter().skip(i + 1) {
+                assert_ne!(a, b);
+            }
+        }
+        assert!(orientations.contains(&grid("ab\ncd").transpose()));
+        assert!(orientations.contains(&grid("ab\ncd").flip_vertical()));
+    }
+}
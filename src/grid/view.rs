@@ -0,0 +1,183 @@
+use std::{fmt, cmp, clone, marker};
+use crate::grid::grid::{Grid, GridOutOfBoundsError, GridShapeError};
+use crate::grid::coordinate::CartesianCoordinate;
+
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right
+}
+
+/// Borrowed rectangular region of a grid, addressed relative to its own origin
+#[derive(fmt::Debug, clone::Clone, marker::Copy)]
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    origin: CartesianCoordinate,
+    width: usize,
+    height: usize,
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn rows(&self) -> impl Iterator<Item = &'a [T]> {
+        let x = self.origin.x;
+        let width = self.width;
+        self.grid.rows()[self.origin.y..self.origin.y + self.height].iter()
+            .map(move |row| &row[x..x + width])
+    }
+}
+
+impl<'a, T: clone::Clone> GridView<'a, T> {
+    pub fn at(&self, pos: &CartesianCoordinate) -> Result<T, GridOutOfBoundsError> {
+        if pos.x >= self.width || pos.y >= self.height {
+            return Err(GridOutOfBoundsError);
+        }
+        Ok(self.grid.rows()[self.origin.y + pos.y][self.origin.x + pos.x].clone())
+    }
+    pub fn to_grid(&self) -> Grid<T> {
+        Grid::new(self.rows().map(|row| row.to_vec()).collect())
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn view(&self, origin: &CartesianCoordinate, width: usize, height: usize) -> Result<GridView<'_, T>, GridOutOfBoundsError> {
+        if origin.y + height > self.height() {
+            return Err(GridOutOfBoundsError);
+        }
+        if self.rows()[origin.y..origin.y + height].iter().any(|row| origin.x + width > row.len()) {
+            return Err(GridOutOfBoundsError);
+        }
+        Ok(GridView{
+            grid: self,
+            origin: *origin,
+            width,
+            height,
+        })
+    }
+}
+
+impl<T: clone::Clone> Grid<T> {
+    pub fn crop(&self, origin: &CartesianCoordinate, width: usize, height: usize) -> Result<Grid<T>, GridOutOfBoundsError> {
+        self.view(origin, width, height)
+            .map(|view| view.to_grid())
+    }
+    /// Removes `n` cells from every side
+    pub fn strip_border(&self, n: usize) -> Result<Grid<T>, GridOutOfBoundsError> {
+        let width = self.width()?;
+        let height = self.height();
+        if 2 * n > width || 2 * n > height {
+            return Err(GridOutOfBoundsError);
+        }
+        self.crop(&CartesianCoordinate::new(n, n), width - 2 * n, height - 2 * n)
+    }
+    /// Top and bottom edges are read left to right, left and right edges top to bottom
+    pub fn edge(&self, side: Side) -> Vec<T> {
+        let rows = self.rows();
+        match side {
+            Side::Top => rows.first().cloned().unwrap_or_default(),
+            Side::Bottom => rows.last().cloned().unwrap_or_default(),
+            Side::Left => rows.iter()
+                .filter_map(|row| row.first().cloned())
+                .collect(),
+            Side::Right => rows.iter()
+                .filter_map(|row| row.last().cloned())
+                .collect(),
+        }
+    }
+    /// Stitches a 2D arrangement of grids into one. Grids in the same row of
+    /// `parts` must share a height, and every composed row must share a width.
+    pub fn compose(parts: &[Vec<Grid<T>>]) -> Result<Grid<T>, GridShapeError> {
+        let mut composed: Vec<Vec<T>> = Vec::new();
+        for part_row in parts {
+            let height = match part_row.first() {
+                Some(part) => part.height(),
+                None => return Err(GridShapeError),
+            };
+            if part_row.iter().any(|part| part.height() != height) {
+                return Err(GridShapeError);
+            }
+            for y in 0..height {
+                composed.push(part_row.iter()
+                    .flat_map(|part| part.rows()[y].iter().cloned())
+                    .collect());
+            }
+        }
+        if composed.iter().any(|row| row.len() != composed[0].len()) {
+            return Err(GridShapeError);
+        }
+        Ok(Grid::new(composed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> Grid<char> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn view() {
+        let grid = grid("abcd\nefgh\nijkl");
+        let view = grid.view(&CartesianCoordinate::new(1, 1), 2, 2).unwrap();
+        assert_eq!(view.width(), 2);
+        assert_eq!(view.height(), 2);
+        assert_eq!(view.at(&CartesianCoordinate::new(0, 0)).unwrap(), 'f');
+        assert_eq!(view.at(&CartesianCoordinate::new(1, 1)).unwrap(), 'k');
+        assert!(view.at(&CartesianCoordinate::new(2, 0)).is_err());
+    }
+
+    #[test]
+    fn view_out_of_bounds() {
+        let grid = grid("abcd\nefgh\nijkl");
+        assert!(grid.view(&CartesianCoordinate::new(3, 0), 2, 1).is_err());
+        assert!(grid.view(&CartesianCoordinate::new(0, 2), 1, 2).is_err());
+        assert!(grid.view(&CartesianCoordinate::new(0, 0), 4, 3).is_ok());
+    }
+
+    #[test]
+    fn crop() {
+        let grid = grid("abcd\nefgh\nijkl");
+        assert_eq!(grid.crop(&CartesianCoordinate::new(2, 0), 2, 3).unwrap(), "cd\ngh\nkl".parse().unwrap());
+    }
+
+    #[test]
+    fn strip_border() {
+        let grid = grid("abcd\nefgh\nijkl\nmnop");
+        assert_eq!(grid.strip_border(1).unwrap(), "fg\njk".parse().unwrap());
+        assert_eq!(grid.strip_border(2).unwrap().height(), 0);
+        assert!(grid.strip_border(3).is_err());
+    }
+
+    #[test]
+    fn edge() {
+        let grid = grid("abc\ndef\nghi");
+        assert_eq!(grid.edge(Side::Top), vec!['a', 'b', 'c']);
+        assert_eq!(grid.edge(Side::Bottom), vec!['g', 'h', 'i']);
+        assert_eq!(grid.edge(Side::Left), vec!['a', 'd', 'g']);
+        assert_eq!(grid.edge(Side::Right), vec!['c', 'f', 'i']);
+    }
+
+    #[test]
+    fn compose() {
+        let parts = vec![
+            vec![grid("ab\nef"), grid("c\ng")],
+            vec![grid("ij"), grid("k")],
+        ];
+        assert_eq!(Grid::compose(&parts).unwrap(), grid("abc\nefg\nijk"));
+    }
+
+    #[test]
+    fn compose_mismatched_shapes() {
+        assert!(Grid::compose(&[vec![grid("ab\nef"), grid("c")]]).is_err());
+        assert!(Grid::compose(&[vec![grid("ab")], vec![grid("abc")]]).is_err());
+    }
+}
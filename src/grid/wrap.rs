@@ -0,0 +1,109 @@
+use std::{fmt, cmp, clone, marker};
+use crate::grid::grid::{Grid, GridOutOfBoundsError};
+use crate::grid::coordinate::CartesianCoordinate;
+
+#[derive(fmt::Debug, clone::Clone, marker::Copy, cmp::PartialEq, cmp::Eq)]
+pub enum Wrap {
+    Horizontal,
+    Vertical,
+    Both
+}
+
+/// Grid where coordinates past an edge on a wrapping axis continue from the opposite edge
+#[derive(fmt::Debug, clone::Clone, marker::Copy)]
+pub struct WrappingGrid<'a, T> {
+    grid: &'a Grid<T>,
+    wrap: Wrap,
+}
+
+impl<T> Grid<T> {
+    pub fn wrapping(&self, wrap: Wrap) -> WrappingGrid<'_, T> {
+        WrappingGrid{
+            grid: self,
+            wrap,
+        }
+    }
+}
+
+impl<'a, T> WrappingGrid<'a, T> {
+    /// The in-bounds coordinate that `(x, y)` refers to
+    pub fn translate(&self, x: isize, y: isize) -> Result<CartesianCoordinate, GridOutOfBoundsError> {
+        let width = self.grid.width()? as isize;
+        let height = self.grid.height() as isize;
+        // Nothing to wrap around to on an empty axis
+        if width == 0 || height == 0 {
+            return Err(GridOutOfBoundsError);
+        }
+        let x = match self.wrap {
+            Wrap::Horizontal | Wrap::Both => x.rem_euclid(width),
+            Wrap::Vertical => x,
+        };
+        let y = match self.wrap {
+            Wrap::Vertical | Wrap::Both => y.rem_euclid(height),
+            Wrap::Horizontal => y,
+        };
+        if x < 0 || x >= width || y < 0 || y >= height {
+            return Err(GridOutOfBoundsError);
+        }
+        Ok(CartesianCoordinate::new(x as usize, y as usize))
+    }
+}
+
+impl<'a, T: clone::Clone> WrappingGrid<'a, T> {
+    pub fn at(&self, pos: &CartesianCoordinate) -> Result<T, GridOutOfBoundsError> {
+        self.at_offset(pos, 0, 0)
+    }
+    pub fn at_offset(&self, pos: &CartesianCoordinate, dx: isize, dy: isize) -> Result<T, GridOutOfBoundsError> {
+        let translated = self.translate(pos.x as isize + dx, pos.y as isize + dy)?;
+        self.grid.at(&translated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn horizontal() {
+        let grid: Grid<char> = "abc\ndef".parse().unwrap();
+        let wrapping = grid.wrapping(Wrap::Horizontal);
+        assert_eq!(wrapping.at(&CartesianCoordinate::new(3, 0)).unwrap(), 'a');
+        assert_eq!(wrapping.at(&CartesianCoordinate::new(7, 1)).unwrap(), 'e');
+        assert_eq!(wrapping.at_offset(&CartesianCoordinate::new(0, 1), -1, 0).unwrap(), 'f');
+        assert!(wrapping.at(&CartesianCoordinate::new(0, 2)).is_err());
+        assert!(wrapping.at_offset(&CartesianCoordinate::new(0, 0), 0, -1).is_err());
+    }
+
+    #[test]
+    fn vertical() {
+        let grid: Grid<char> = "abc\ndef".parse().unwrap();
+        let wrapping = grid.wrapping(Wrap::Vertical);
+        assert_eq!(wrapping.at(&CartesianCoordinate::new(1, 2)).unwrap(), 'b');
+        assert_eq!(wrapping.at_offset(&CartesianCoordinate::new(2, 0), 0, -3).unwrap(), 'f');
+        assert!(wrapping.at(&CartesianCoordinate::new(3, 0)).is_err());
+    }
+
+    #[test]
+    fn empty_axis() {
+        let grid: Grid<char> = "\n\n".parse().unwrap();
+        assert_eq!(grid.width().unwrap(), 0);
+        assert!(grid.wrapping(Wrap::Both).translate(1, 1).is_err());
+        assert!(grid.wrapping(Wrap::Horizontal).translate(0, 0).is_err());
+    }
+
+    #[test]
+    fn both() {
+        let grid: Grid<char> = "abc\ndef".parse().unwrap();
+        let wrapping = grid.wrapping(Wrap::Both);
+        assert_eq!(wrapping.at(&CartesianCoordinate::new(4, 3)).unwrap(), 'e');
+        assert_eq!(wrapping.at_offset(&CartesianCoordinate::new(0, 0), -1, -1).unwrap(), 'f');
+        assert_eq!(wrapping.translate(-7, 5).unwrap(), CartesianCoordinate::new(2, 1));
+    }
+
+    #[test]
+    fn translate() {
+        let grid: Grid<char> = "...........".parse().unwrap();
+        let wrapping = grid.wrapping(Wrap::Horizontal);
+        assert_eq!(wrapping.translate(11, 0).unwrap(), CartesianCoordinate::new(0, 0));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod grid;
+pub mod console;
+pub mod expression;
+pub mod solutions;
@@ -1,12 +1,19 @@
 use std::{env, fs};
 
-mod grid;
-mod solutions;
+use aoc_2020::solutions;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     let day = args[1].as_str();
     let input = read_puzzle_input(day);
+    match (day, args.get(2).map(String::as_str)) {
+        ("8", Some("debug")) => return solutions::day8::debug(&input),
+        ("8", Some("profile")) => return solutions::day8::profile(&input),
+        ("11", Some("play")) => return solutions::day11::play(&input, args.get(3).map_or("", String::as_str)),
+        ("17", Some("frames")) => return solutions::day17::save_frames(&input, args.get(3).map_or("frames", String::as_str)),
+        ("17", Some("dimensions")) => return solutions::day17::run_in_dimensions(&input, args.get(3).map_or("", String::as_str)),
+        _ => {}
+    }
     match args[1].as_str() {
         "1" => solutions::day1::run(input),
         "2" => solutions::day2::run(input),
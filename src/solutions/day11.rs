@@ -1,80 +1,84 @@
+use std::io;
+use std::convert::TryFrom;
 use crate::grid::grid::Grid;
-use crate::grid::coordinate::CartesianCoordinate;
+use crate::grid::coordinate::{CartesianCoordinate, Point};
+use crate::grid::record::Recorder;
+use crate::grid::render::{Renderer, Rgb, Style};
+use crate::grid::simulation::{Simulation, Cycle};
 
 pub fn run(input: &str) {
-    println!("Part 1: {}", part_1(input));
-    println!("Part 2: {}", part_2(input));
+    println!("Part 1: {}", report(part_1(input)));
+    println!("Part 2: {}", report(part_2(input)));
 }
 
-fn part_1(input: &str) -> usize {
-    let mut grid: Grid<char> = input.parse().unwrap();
-    let coordinates = grid.coordinates();
-    loop {
-        let mut updates: Vec<(CartesianCoordinate, char)> = Vec::new();
-        coordinates.iter()
-            .map(|coord| (coord, grid.at(coord).unwrap()))
-            .filter(|c| c.1 == '#' || c.1 == 'L')
-            .for_each(|(coord, element)| {
-                let adjacent_occupied = grid.adjacent_to(&coord, '#');
-                if adjacent_occupied == 0 && element == 'L' {
-                    updates.push((*coord, '#'))
-                }
-                if adjacent_occupied >= 4 && element == '#' {
-                    updates.push((*coord, 'L'));
-                }
-            });
-        if updates.is_empty() {
-            break;
-        }
-        for update in updates {
-            grid.set(&update.0, update.1).unwrap();
-        }
+fn report(occupied: Result<usize, Cycle>) -> String {
+    match occupied {
+        Ok(occupied) => occupied.to_string(),
+        Err(cycle) => format!("never settles, repeats every {} rounds from round {}", cycle.period, cycle.start),
     }
-    grid.number_of('#')
 }
 
-fn part_2(input: &str) -> usize {
-    let mut grid: Grid<char> = input.parse().unwrap();
-    let coordinates = grid.coordinates();
-    loop {
-        let mut updates: Vec<(CartesianCoordinate, char)> = Vec::new();
-        coordinates.iter()
-            .map(|coord| (coord, grid.at(coord).unwrap()))
-            .filter(|c| c.1 == '#' || c.1 == 'L') 
-            .for_each(|(coord, element)| {
-                let visible_occupied = number_visible_occupied_seats(&grid, coord);
-                if visible_occupied == 0 && element == 'L' {
-                    updates.push((*coord, '#'));
-                }
-                if visible_occupied >= 5 && element == '#' {
-                    updates.push((*coord, 'L'));
-                }
-            });
-        if updates.is_empty() {
-            break;
-        }
-        for update in updates {
-            grid.set(&update.0, update.1).unwrap();
+/// Plays the rounds of part 1 in the terminal, `fps` rounds a second
+pub fn play(input: &str, fps: &str) {
+    let mut recorder = Recorder::new();
+    let occupied = settle(input, 4, |grid, coord| grid.adjacent_to(coord, '#'), Some(&mut recorder));
+    let renderer = Renderer::new(seat_style);
+    recorder.play(&renderer, &mut io::stdout(), fps.parse().unwrap_or(10)).unwrap();
+    println!("Occupied: {}", report(occupied));
+}
+
+fn seat_style(seat: &char) -> Style {
+    let colour = match seat {
+        '#' => Rgb(220, 50, 47),
+        'L' => Rgb(133, 153, 0),
+        _ => Rgb(88, 110, 117),
+    };
+    Style{symbol: *seat, colour}
+}
+
+fn part_1(input: &str) -> Result<usize, Cycle> {
+    settle(input, 4, |grid, coord| grid.adjacent_to(coord, '#'), None)
+}
+
+fn part_2(input: &str) -> Result<usize, Cycle> {
+    settle(input, 5, number_visible_occupied_seats, None)
+}
+
+/// Applies the seating rules until nothing changes and returns the number of occupied
+/// seats, or the cycle if the seating keeps changing. A seat empties once
+/// `occupied_around` reaches `tolerance`.
+fn settle<F>(input: &str, tolerance: usize, occupied_around: F, recorder: Option<&mut Recorder<char>>) -> Result<usize, Cycle>
+    where F: Fn(&Grid<char>, &CartesianCoordinate) -> usize + 'static {
+    let grid: Grid<char> = input.parse().unwrap();
+    let rule = move |grid: &Grid<char>, coord: &CartesianCoordinate| {
+        let seat = grid.at(coord).unwrap();
+        match seat {
+            'L' if occupied_around(grid, coord) == 0 => '#',
+            '#' if occupied_around(grid, coord) >= tolerance => 'L',
+            _ => seat,
         }
+    };
+    let mut simulation = Simulation::new(grid, rule, |seat| *seat == '#');
+    let cycle = simulation.run(recorder);
+    if !cycle.is_stable() {
+        return Err(cycle);
     }
-    grid.number_of('#')
+    Ok(simulation.grid().number_of('#'))
 }
 
 fn number_visible_occupied_seats(grid: &Grid<char>, from: &CartesianCoordinate) -> usize {
-    let directions = vec![(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
-    let width = grid.width().unwrap() as isize;
-    let height = grid.height() as isize;
+    let directions = [(1, 0), (-1, 0), (0, 1), (0, -1), (1, 1), (1, -1), (-1, 1), (-1, -1)];
     let mut num_occupied = 0;
-    for (x_step, y_step) in directions {
-        let mut x = from.x as isize;
-        let mut y = from.y as isize;
+    for (x_step, y_step) in directions.iter() {
+        let step = Point::new(*x_step, *y_step);
+        let mut pos = Point::from(*from);
         loop {
-            x += x_step;
-            y += y_step;
-            if (x < 0 || x >= width) || (y < 0 || y >= height) {
-                break;
-            }
-            match grid.at(&CartesianCoordinate::new(x as usize, y as usize)).unwrap() {
+            pos += step;
+            let seat = match CartesianCoordinate::try_from(pos).ok().and_then(|pos| grid.at(&pos).ok()) {
+                Some(seat) => seat,
+                None => break,
+            };
+            match seat {
                 '#' => {
                     num_occupied += 1;
                     break;
@@ -101,12 +105,30 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        assert_eq!(part_1(&read_test_input()), 37);
+        assert_eq!(part_1(&read_test_input()), Ok(37));
     }
 
     #[test]
     fn test_part_2() {
-        assert_eq!(part_2(&read_test_input()), 26);
+        assert_eq!(part_2(&read_test_input()), Ok(26));
+    }
+
+    #[test]
+    fn test_recorded_generations() {
+        let mut recorder = Recorder::new();
+        let occupied = settle(&read_test_input(), 4, |grid, coord| grid.adjacent_to(coord, '#'), Some(&mut recorder));
+        assert_eq!(occupied, Ok(37));
+        assert_eq!(recorder.len(), 6);
+        assert_eq!(recorder.frames()[0], read_test_input().parse().unwrap());
+        assert_eq!(recorder.frames()[1].number_of('L'), 0);
+        assert_eq!(recorder.frames()[5].number_of('#'), 37);
+    }
+
+    #[test]
+    fn test_oscillating_seats() {
+        let occupied = settle(&read_test_input(), 0, |grid, coord| grid.adjacent_to(coord, '#'), None);
+        assert_eq!(occupied, Err(Cycle{start: 0, period: 2}));
+        assert_eq!(report(occupied), "never settles, repeats every 2 rounds from round 0");
     }
 
     #[test]
@@ -1,4 +1,5 @@
 use regex::Regex;
+use crate::grid::coordinate::{Point, Direction};
 
 pub fn run(input: &str) {
     println!("Part 1: {}", part_1(input));
@@ -13,81 +14,63 @@ fn parse_input(input: &str) -> Vec<(char, i64)> {
 }
 
 fn part_1(input: &str) -> i64 {
-    let mut x = 0i64;
-    let mut y = 0i64;
-    let mut rotation = 0i64;
+    let mut ship = Point::default();
+    let mut facing = Direction::East;
     parse_input(input).iter()
         .for_each(|(action, value)| {
             match action {
-                'N' => y += value,
-                'S' => y -= value,
-                'E' => x += value,
-                'W' => x -= value,
-                'L' => rotation -= value,
-                'R' => rotation += value,
-                'F' => match rotation % 360 {
-                    0 => x += value,
-                    90 | -270 => y -= value,
-                    180 | -180 => x -= value,
-                    270 | -90 => y += value,
-                    _ => {},
-                },
+                'N' => ship += Direction::North.offset() * *value,
+                'S' => ship += Direction::South.offset() * *value,
+                'E' => ship += Direction::East.offset() * *value,
+                'W' => ship += Direction::West.offset() * *value,
+                'L' => (0..value / 90).for_each(|_| facing = facing.turn_left()),
+                'R' => (0..value / 90).for_each(|_| facing = facing.turn_right()),
+                'F' => ship += facing.offset() * *value,
                 _ => {}
             };
         });
-    x.abs() + y.abs()
+    ship.manhattan(&Point::default())
 }
 
 fn part_2(input: &str) -> i64 {
-    let mut waypoint_x = 10i64;
-    let mut waypoint_y = 1i64;
-    let mut x = 0i64;
-    let mut y = 0i64;
+    let mut waypoint = Point::new(10, -1);
+    let mut ship = Point::default();
     parse_input(input).iter()
         .for_each(|(action, value)| {
             match action {
-                'N' => waypoint_y += value,
-                'S' => waypoint_y -= value,
-                'E' => waypoint_x += value,
-                'W' => waypoint_x -= value,
-                'L' => match rotate_waypoint_left(waypoint_x, waypoint_y, *value) {
-                    Ok((new_x, new_y)) => {
-                        waypoint_x = new_x;
-                        waypoint_y = new_y;
-                    },
-                    Err(e) => panic!(e)
+                'N' => waypoint += Direction::North.offset() * *value,
+                'S' => waypoint += Direction::South.offset() * *value,
+                'E' => waypoint += Direction::East.offset() * *value,
+                'W' => waypoint += Direction::West.offset() * *value,
+                'L' => match rotate_waypoint_left(waypoint, *value) {
+                    Ok(rotated) => waypoint = rotated,
+                    Err(e) => panic!("{}", e)
                 },
-                'R' => match rotate_waypoint_right(waypoint_x, waypoint_y, *value) {
-                    Ok((new_x, new_y)) => {
-                        waypoint_x = new_x;
-                        waypoint_y = new_y;
-                    },
-                    Err(e) => panic!(e)
+                'R' => match rotate_waypoint_right(waypoint, *value) {
+                    Ok(rotated) => waypoint = rotated,
+                    Err(e) => panic!("{}", e)
                 } 
-                'F' => {
-                    x += value * waypoint_x;
-                    y += value * waypoint_y;
-                },
+                'F' => ship += waypoint * *value,
                 _ => {}
             }
         });
-    x.abs() + y.abs()
+    ship.manhattan(&Point::default())
 }
 
-fn rotate_waypoint_right(x: i64, y: i64, value: i64) -> Result<(i64, i64), String> {
+fn rotate_waypoint_right(waypoint: Point, value: i64) -> Result<Point, String> {
     match value {
-        90 => Ok((0 + y, 0 - x)),
-        180 => Ok((0 - x, 0 - y)),
-        270 => Ok((0 - y, x)),
+        90 => Ok(waypoint.rotate_right()),
+        180 => Ok(-waypoint),
+        270 => Ok(waypoint.rotate_left()),
         _ => Err(format!("Unrecognized rotation: {}", value)) 
     }
 }
 
-fn rotate_waypoint_left(x: i64, y: i64, value: i64) -> Result<(i64, i64), String> {
+fn rotate_waypoint_left(waypoint: Point, value: i64) -> Result<Point, String> {
     match value {
-        90 => Ok((0 - y, x)),
-        180 => Ok((0 - x, 0 - y)),
-        270 => Ok((0 + y, 0 - x)),
+        90 => Ok(waypoint.rotate_left()),
+        180 => Ok(-waypoint),
+        270 => Ok(waypoint.rotate_right()),
         _ => Err(format!("Unrecognized rotation: {}", value))
     }
 }
@@ -110,33 +93,33 @@ mod tests {
 
     #[test]
     fn test_rotate_waypoint_right() {
-        assert_eq!(rotate_waypoint_right(10, 4, 90).unwrap(), (4, -10));
-        assert_eq!(rotate_waypoint_right(4, -10, 90).unwrap(), (-10, -4));
-        assert_eq!(rotate_waypoint_right(-10, -4, 90).unwrap(), (-4, 10));
-        assert_eq!(rotate_waypoint_right(-4, 10, 90).unwrap(), (10, 4));
-        assert_eq!(rotate_waypoint_right(10, 4, 180).unwrap(), (-10, -4));
-        assert_eq!(rotate_waypoint_right(4, -10, 180).unwrap(), (-4, 10));
-        assert_eq!(rotate_waypoint_right(-10, -4, 180).unwrap(), (10, 4));
-        assert_eq!(rotate_waypoint_right(-4, 10, 180).unwrap(), (4, -10));
-        assert_eq!(rotate_waypoint_right(10, 4, 270).unwrap(), (-4, 10));
-        assert_eq!(rotate_waypoint_right(4, -10, 270).unwrap(), (10, 4));
-        assert_eq!(rotate_waypoint_right(-10, -4, 270).unwrap(), (4, -10));
-        assert_eq!(rotate_waypoint_right(-4, 10, 270).unwrap(), (-10, -4));
+        assert_eq!(rotate_waypoint_right(Point::new(10, -4), 90).unwrap(), Point::new(4, 10));
+        assert_eq!(rotate_waypoint_right(Point::new(4, 10), 90).unwrap(), Point::new(-10, 4));
+        assert_eq!(rotate_waypoint_right(Point::new(-10, 4), 90).unwrap(), Point::new(-4, -10));
+        assert_eq!(rotate_waypoint_right(Point::new(-4, -10), 90).unwrap(), Point::new(10, -4));
+        assert_eq!(rotate_waypoint_right(Point::new(10, -4), 180).unwrap(), Point::new(-10, 4));
+        assert_eq!(rotate_waypoint_right(Point::new(4, 10), 180).unwrap(), Point::new(-4, -10));
+        assert_eq!(rotate_waypoint_right(Point::new(-10, 4), 180).unwrap(), Point::new(10, -4));
+        assert_eq!(rotate_waypoint_right(Point::new(-4, -10), 180).unwrap(), Point::new(4, 10));
+        assert_eq!(rotate_waypoint_right(Point::new(10, -4), 270).unwrap(), Point::new(-4, -10));
+        assert_eq!(rotate_waypoint_right(Point::new(4, 10), 270).unwrap(), Point::new(10, -4));
+        assert_eq!(rotate_waypoint_right(Point::new(-10, 4), 270).unwrap(), Point::new(4, 10));
+        assert_eq!(rotate_waypoint_right(Point::new(-4, -10), 270).unwrap(), Point::new(-10, 4));
     }
 
     #[test]
     fn test_rotate_waypoint_left() {
-        assert_eq!(rotate_waypoint_left(10, 4, 90).unwrap(), (-4, 10));
-        assert_eq!(rotate_waypoint_left(-4, 10, 90).unwrap(), (-10, -4));
-        assert_eq!(rotate_waypoint_left(-10, -4, 90).unwrap(), (4, -10));
-        assert_eq!(rotate_waypoint_left(4, -10, 90).unwrap(), (10, 4));
-        assert_eq!(rotate_waypoint_left(10, 4, 180).unwrap(), (-10, -4));
-        assert_eq!(rotate_waypoint_left(-4, 10, 180).unwrap(), (4, -10));
-        assert_eq!(rotate_waypoint_left(-10, -4, 180).unwrap(), (10, 4));
-        assert_eq!(rotate_waypoint_left(4, -10, 180).unwrap(), (-4, 10));
-        assert_eq!(rotate_waypoint_left(10, 4, 270).unwrap(), (4, -10));
-        assert_eq!(rotate_waypoint_left(-4, 10, 270).unwrap(), (10, 4));
-        assert_eq!(rotate_waypoint_left(-10, -4, 270).unwrap(), (-4, 10));
-        assert_eq!(rotate_waypoint_left(4, -10, 270).unwrap(), (-10, -4));
+        assert_eq!(rotate_waypoint_left(Point::new(10, -4), 90).unwrap(), Point::new(-4, -10));
+        assert_eq!(rotate_waypoint_left(Point::new(-4, -10), 90).unwrap(), Point::new(-10, 4));
+        assert_eq!(rotate_waypoint_left(Point::new(-10, 4), 90).unwrap(), Point::new(4, 10));
+        assert_eq!(rotate_waypoint_left(Point::new(4, 10), 90).unwrap(), Point::new(10, -4));
+        assert_eq!(rotate_waypoint_left(Point::new(10, -4), 180).unwrap(), Point::new(-10, 4));
+        assert_eq!(rotate_waypoint_left(Point::new(-4, -10), 180).unwrap(), Point::new(4, 10));
+        assert_eq!(rotate_waypoint_left(Point::new(-10, 4), 180).unwrap(), Point::new(10, -4));
+        assert_eq!(rotate_waypoint_left(Point::new(4, 10), 180).unwrap(), Point::new(-4, -10));
+        assert_eq!(rotate_waypoint_left(Point::new(10, -4), 270).unwrap(), Point::new(4, 10));
+        assert_eq!(rotate_waypoint_left(Point::new(-4, -10), 270).unwrap(), Point::new(10, -4));
+        assert_eq!(rotate_waypoint_left(Point::new(-10, 4), 270).unwrap(), Point::new(-4, -10));
+        assert_eq!(rotate_waypoint_left(Point::new(4, 10), 270).unwrap(), Point::new(-10, 4));
     }
 }
\ No newline at end of file
@@ -1,6 +1,36 @@
-use std::{ops::RangeInclusive, collections::HashSet};
+use crate::grid::automaton::{Automaton, Cell, Rule};
+use crate::grid::grid::Grid;
+use crate::grid::record::Recorder;
+use crate::grid::render::{Renderer, Rgb, Style};
 
-type Cube = Vec<isize>;
+const CYCLES: usize = 6;
+
+/// Cube in `D` dimensions, stored by its absolute value on every axis after x and y.
+/// The starting slice lies at 0 on those axes, so the space stays mirror-symmetric in
+/// each of them and only the non-negative half needs to be simulated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SymmetricCube<const D: usize>([i32; D]);
+
+impl<const D: usize> Cell for SymmetricCube<D> {
+    fn for_each_neighbour<F: FnMut(Self, usize)>(&self, mut f: F) {
+        self.0.for_each_neighbour(|neighbour, _weight| {
+            let mut weight = 1;
+            for pair in self.0[2..].iter().zip(neighbour[2..].iter()) {
+                match pair {
+                    // Counted through the mirrored neighbour at 1 instead
+                    (0, -1) => return,
+                    // Both this cube and its mirror image at -1 touch 0
+                    (1, 0) => weight *= 2,
+                    _ => {}
+                }
+            }
+            f(SymmetricCube(neighbour), weight);
+        });
+    }
+    fn multiplicity(&self) -> usize {
+        1 << self.0[2..].iter().filter(|d| **d != 0).count()
+    }
+}
 
 pub fn run(input: &str) {
     println!("Part 1: {}", part_1(input));
@@ -8,107 +38,97 @@ pub fn run(input: &str) {
 }
 
 fn part_1(input: &str) -> u64 {
-    simulate(input, 2)
+    simulate::<3>(input, CYCLES, None)
 }
 
 fn part_2(input: &str) -> u64 {
-    simulate(input, 3)
+    simulate::<4>(input, CYCLES, None)
 }
 
-fn simulate(input: &str, dimensions: usize) -> u64 {
-    let mut space = parse(input, dimensions);
-    let mut border = calculate_border(&space, dimensions); 
-    for _i in 0..6 {
-        let mut next_space: HashSet<Cube> = HashSet::new();
-        apply_changes(&space, &mut next_space, &border, dimensions, 0, Vec::new());
-        border = calculate_border(&next_space, dimensions);
-        space = next_space;
+/// Active cubes after the boot cycles in 3 to 6 dimensions
+fn in_dimensions(input: &str, dimensions: usize) -> Option<u64> {
+    match dimensions {
+        3 => Some(simulate::<3>(input, CYCLES, None)),
+        4 => Some(simulate::<4>(input, CYCLES, None)),
+        5 => Some(simulate::<5>(input, CYCLES, None)),
+        6 => Some(simulate::<6>(input, CYCLES, None)),
+        _ => None,
     }
-    space.len() as u64
 }
 
-fn calculate_border(space: &HashSet<Cube>, dimensions: usize) -> Vec<RangeInclusive<isize>> {
-    let mut border: Vec<RangeInclusive<isize>> = Vec::new();
-    for d in 0..dimensions+1 {
-        let values: Cube = space.iter()
-            .map(|coord| *coord.get(d).unwrap())
-            .collect();
-        border.push(RangeInclusive::new(
-            values.iter().min().unwrap_or(&0) - 1,
-            values.iter().max().unwrap_or(&0) + 1
-        ))
-    }
-    border
+/// Runs the puzzle in a given number of dimensions
+pub fn run_in_dimensions(input: &str, dimensions: &str) {
+    match dimensions.parse().ok().and_then(|dimensions| in_dimensions(input, dimensions)) {
+        Some(active) => println!("{}D: {}", dimensions, active),
+        None => println!("Only 3 to 6 dimensions are supported, not {}", dimensions),
+    }
 }
 
-fn parse(input: &str, dimensions: usize) -> HashSet<Cube> {
-    let mut space: HashSet<Cube> = HashSet::new();
-    input.lines().enumerate().for_each(|(y, line)| {
-        line.chars().enumerate().for_each(|(x, c)| {
-            match c {
-                '#' => {
-                    let mut coordinate = vec![0isize; dimensions+1];
-                    coordinate[dimensions-1] = y as isize;
-                    coordinate[dimensions] = x as isize;
-                    space.insert(coordinate);
-                },
-                _ => {}
-            };
-        });
+/// Saves the slice through z = 0 of every cycle in 3 dimensions as PPM images in `directory`
+pub fn save_frames(input: &str, directory: &str) {
+    let mut recorder = Recorder::new();
+    simulate::<3>(input, CYCLES, Some(&mut recorder));
+    let renderer = Renderer::new(|cube: &char| Style{
+        symbol: *cube,
+        colour: if *cube == '#' { Rgb(255, 255, 255) } else { Rgb(0, 0, 0) },
     });
-    space
-}
-
-fn apply_changes(space: &HashSet<Cube>, next_space: &mut HashSet<Cube>, border: &Vec<RangeInclusive<isize>>, target_dimension: usize, dimension: usize, coordinate: Cube) {
-    if dimension > target_dimension {
-        let active_neighbours = active_neighbours(&coordinate.clone(), space, target_dimension, 0, Vec::new());
-        match space.get(&coordinate) {
-            Some(_cube) => {
-                if active_neighbours == 2 || active_neighbours == 3 {
-                    next_space.insert(coordinate);
-                }
-            },
-            None => {
-                if active_neighbours == 3 {
-                    next_space.insert(coordinate);
-                }
-            },
-        }
-        return;
-    }
-    for n in border.get(dimension).unwrap().clone() {
-        let mut coordinate = coordinate.clone();
-        coordinate.push(n);
-        apply_changes(space, next_space, border, target_dimension, dimension + 1, coordinate);
+    match recorder.save_ppm_frames(&renderer, directory, 8) {
+        Ok(paths) => println!("Saved {} frames to {}", paths.len(), directory),
+        Err(e) => println!("Could not save frames to {}: {}", directory, e),
     }
 }
 
-fn active_neighbours(source: &Cube, space: &HashSet<Cube>, target_dimension: usize, dimension: usize, coordinate: Cube) -> usize {
-    if coordinate == *source {
-        return 0;
+/// Runs the cubes for `cycles` generations. A `recorder` receives the slice through 0 on
+/// every axis after x and y, sized to everything that slice can reach in that many cycles.
+fn simulate<const D: usize>(input: &str, cycles: usize, mut recorder: Option<&mut Recorder<char>>) -> u64 {
+    let cubes = parse::<D>(input).into_iter().map(SymmetricCube);
+    let mut space = Automaton::new(cubes, Rule::conway());
+    if let Some(recorder) = recorder.as_deref_mut() {
+        recorder.record(&slice(&space, input, cycles));
     }
-    if dimension > target_dimension {
-        match space.contains(&coordinate) {
-            true => return 1,
-            false => return 0,
+    for _i in 0..cycles {
+        space.step();
+        if let Some(recorder) = recorder.as_deref_mut() {
+            recorder.record(&slice(&space, input, cycles));
         }
     }
-    let mut sum = 0usize;
-    let s = source.get(dimension).unwrap();
-    for n in s - 1..=s + 1 {
-        let mut coordinate = coordinate.clone();
-        coordinate.push(n);
-        if sum > 3 {
-            break;
+    space.population() as u64
+}
+
+fn slice<const D: usize>(space: &Automaton<SymmetricCube<D>>, input: &str, margin: usize) -> Grid<char> {
+    let height = input.lines().count() + 2 * margin;
+    let width = input.lines().map(str::len).max().unwrap_or(0) + 2 * margin;
+    let mut cells = vec![vec!['.'; width]; height];
+    for cube in space.active() {
+        if cube.0[2..].iter().any(|d| *d != 0) {
+            continue;
         }
-        sum += active_neighbours(source, space, target_dimension, dimension + 1, coordinate);
+        let x = cube.0[0] + margin as i32;
+        let y = cube.0[1] + margin as i32;
+        cells[y as usize][x as usize] = '#';
     }
-    sum
+    Grid::new(cells)
+}
+
+fn parse<const D: usize>(input: &str) -> Vec<[i32; D]> {
+    let mut cubes: Vec<[i32; D]> = Vec::new();
+    input.lines().enumerate().for_each(|(y, line)| {
+        line.chars().enumerate().for_each(|(x, c)| {
+            if c == '#' {
+                let mut cube = [0i32; D];
+                cube[0] = x as i32;
+                cube[1] = y as i32;
+                cubes.push(cube);
+            }
+        });
+    });
+    cubes
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashSet;
 
     #[test]
     fn part_1_test() {
@@ -116,9 +136,75 @@ mod tests {
         assert_eq!(part_1(input), 112);
     }
 
-    // #[test]
+    #[test]
     fn part_2_test() {
         let input = ".#.\n..#\n###";
         assert_eq!(part_2(input), 848);
     }
+
+    fn simulate_unreduced<const D: usize>(input: &str, cycles: usize) -> HashSet<[i32; D]> {
+        let mut space = Automaton::new(parse::<D>(input), Rule::conway());
+        space.run(cycles);
+        space.active().clone()
+    }
+
+    fn simulate_reduced<const D: usize>(input: &str, cycles: usize) -> HashSet<[i32; D]> {
+        let mut space = Automaton::new(parse::<D>(input).into_iter().map(SymmetricCube), Rule::conway());
+        space.run(cycles);
+        let mut unfolded: HashSet<[i32; D]> = HashSet::new();
+        for cube in space.active() {
+            let mut mirrors = vec![cube.0];
+            for axis in 2..D {
+                for i in 0..mirrors.len() {
+                    let mut mirror = mirrors[i];
+                    mirror[axis] = -mirror[axis];
+                    mirrors.push(mirror);
+                }
+            }
+            assert_eq!(mirrors.iter().collect::<HashSet<_>>().len(), cube.multiplicity());
+            unfolded.extend(mirrors);
+        }
+        unfolded
+    }
+
+    #[test]
+    fn reduced_matches_unreduced() {
+        for input in &[".#.\n..#\n###", "##.#\n#..#\n.##."] {
+            for cycles in 0..=CYCLES {
+                assert_eq!(simulate_reduced::<3>(input, cycles), simulate_unreduced::<3>(input, cycles));
+                assert_eq!(simulate_reduced::<4>(input, cycles), simulate_unreduced::<4>(input, cycles));
+            }
+        }
+    }
+
+    #[test]
+    fn reduced_matches_unreduced_in_5d() {
+        let input = ".#.\n..#\n###";
+        assert_eq!(simulate_reduced::<5>(input, 3), simulate_unreduced::<5>(input, 3));
+    }
+
+    #[test]
+    #[ignore = "slow without optimisations, run with cargo test --release -- --ignored"]
+    fn puzzle_input_in_higher_dimensions() {
+        let input = std::fs::read_to_string("src/solutions/day17.input").unwrap();
+        assert_eq!(in_dimensions(&input, 5), Some(14728));
+        assert_eq!(in_dimensions(&input, 6), Some(92896));
+        assert_eq!(in_dimensions(&input, 7), None);
+    }
+
+    #[test]
+    fn recorded_slices() {
+        let mut recorder = Recorder::new();
+        simulate::<3>(".#.\n..#\n###", 3, Some(&mut recorder));
+        assert_eq!(recorder.len(), 4);
+        assert_eq!(recorder.frames()[0].to_string(), ".........\n.........\n.........\n....#....\n.....#...\n...###...\n.........\n.........\n.........\n");
+        assert_eq!(recorder.frames()[1].number_of('#'), 5);
+        assert_eq!(recorder.frames()[2].number_of('#'), 9);
+        assert_eq!(recorder.frames()[3].number_of('#'), 8);
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(parse::<3>(".#.\n..#"), vec![[1, 0, 0], [2, 1, 0]]);
+    }
 }
@@ -1,5 +1,6 @@
 use crate::grid::grid::Grid;
 use crate::grid::coordinate::CartesianCoordinate;
+use crate::grid::wrap::Wrap;
 
 pub fn run(input: String) {
     let forest_grid: Grid<char> = input.parse().unwrap();
@@ -13,24 +14,18 @@ pub fn run(input: String) {
 }
 
 fn find_tree_obstacles(grid: &Grid<char>, move_x: usize, move_y: usize) -> u32 {
+    let forest = grid.wrapping(Wrap::Horizontal);
     let mut current_pos = CartesianCoordinate::new(0, 0);
     let mut obstacle_count = 0;
     loop {
         current_pos = CartesianCoordinate::new(current_pos.x + move_x, current_pos.y + move_y);
-        let translated_pos = translate_position(grid.width().unwrap(), &current_pos);
-        if translated_pos.y >= grid.height() {
-            break;
-        }
-        let object_on_grid = grid.at(&CartesianCoordinate::new(translated_pos.x, translated_pos.y)).unwrap();
-        if object_on_grid == '#' {
-            obstacle_count += 1;
+        match forest.at(&current_pos) {
+            Ok('#') => obstacle_count += 1,
+            Ok(_) => {},
+            Err(_) => break,
         }
     }
-    return obstacle_count; 
-}
-
-fn translate_position(width: usize, pos: &CartesianCoordinate) -> CartesianCoordinate {
-    CartesianCoordinate::new(pos.x % width, pos.y)
+    obstacle_count
 }
 
 #[cfg(test)]
@@ -48,10 +43,4 @@ mod tests {
         let grid: Grid<char> = read_test_input().parse().unwrap();
         assert_eq!(find_tree_obstacles(&grid, 3, 1), 7);
     }
-
-    #[test]
-    fn should_translate_pos() {
-        let translated_pos = translate_position(11, &CartesianCoordinate::new(11, 0));
-        assert_eq!(translated_pos, CartesianCoordinate::new(0, 0));
-    }
 }
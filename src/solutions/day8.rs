@@ -1,141 +1,89 @@
-use std::{str, fmt, cmp, collections, clone};
+use std::io;
+use crate::console::instruction::Instruction;
+use crate::console::cfg::{ControlFlowGraph, Flip};
+use crate::console::debugger::Debugger;
+use crate::console::vm::{Vm, Op, Halting, RunOutcome};
+use crate::console::profile::Profile;
 
-#[derive(fmt::Debug)]
-struct ParseInstructionError;
-
-#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
-enum Operation {
-    JMP,
-    ACC,
-    NOP
-}
-impl str::FromStr for Operation {
-    type Err = ParseInstructionError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s[..] {
-            "jmp" => Ok(Operation::JMP),
-            "acc" => Ok(Operation::ACC),
-            "nop" => Ok(Operation::NOP),
-            _ => Err(ParseInstructionError)
-        }
-    }
-}
-
-#[derive(fmt::Debug, cmp::PartialEq, cmp::Eq, clone::Clone)]
-struct Instruction {
-    operation: Operation,
-    argument: i64,
+/// Runs the program on the console and returns how it stopped, with the accumulator at that
+/// point. A `profile` is filled in with statistics about the run.
+fn execute(instructions: &[Instruction], profile: Option<&mut Profile>) -> (RunOutcome, i64) {
+    let mut vm = Vm::console(instructions);
+    let outcome = match profile {
+        Some(profile) => vm.run_observed(&Halting::default(), profile),
+        None => vm.run(&Halting::default()),
+    };
+    (outcome, vm.state().accumulator())
 }
 
-impl str::FromStr for Instruction {
-    type Err = ParseInstructionError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(" ").collect();
-        Ok(Instruction{
-            operation: parts[0].parse()?,
-            argument: parts[1].parse()
-                .or(Err(ParseInstructionError))? 
-        })
+pub fn run(input: &str) {
+    let instructions: Vec<Instruction> = input.lines()
+        .filter_map(|line| line.parse().ok())
+        .collect();
+    match part_1(&instructions) {
+        Some(accumulator) => println!("Part 1: {}", accumulator),
+        None => println!("Part 1: the program does not loop"),
+    }
+    match part_2(&instructions) {
+        Some((flip, accumulator)) => println!("Part 2: {} (changed {} at {} to {})",
+            accumulator, instructions[flip.address], flip.address, flip.replacement),
+        None => println!("Part 2: no single change makes the program terminate"),
     }
 }
 
-fn execute(instructions: &Vec<Instruction>) -> Result<i64, i64> {
-    let mut counter: i64 = 0;
-    let mut accumulator: i64 = 0;
-    let mut past_instructions: collections::HashSet<i64> = collections::HashSet::new();
-    loop {
-        if past_instructions.contains(&counter) {
-            return Err(accumulator);
-        }
-        past_instructions.insert(counter);
-        let instruction = match instructions.get(counter as usize) {
-            Some(instruction) => instruction,
-            None => return Ok(accumulator)
-        };
-        match instruction.operation {
-            Operation::NOP => {
-                counter += 1;
-            },
-            Operation::ACC => {
-                accumulator += instruction.argument;
-                counter += 1
-            },
-            Operation::JMP => {
-                counter += instruction.argument;
-            }
-        }
-    }
+/// Starts an interactive debugger on the puzzle input
+pub fn debug(input: &str) {
+    let instructions: Vec<Instruction> = input.lines()
+        .filter_map(|line| line.parse().ok())
+        .collect();
+    let stdin = io::stdin();
+    Debugger::new(Vm::console(&instructions)).repl(stdin.lock(), io::stdout()).unwrap();
 }
 
-pub fn run(input: &str) {
+/// Prints an annotated listing of the puzzle input as it runs until the loop, then the hottest blocks
+pub fn profile(input: &str) {
     let instructions: Vec<Instruction> = input.lines()
         .filter_map(|line| line.parse().ok())
         .collect();
-    println!("Part 1: {:?}", part_1(&instructions));
-    println!("Part 2: {:?}", part_2(&instructions));
+    let program: Vec<Op> = instructions.iter().map(Op::from).collect();
+    let mut profile = Profile::for_program(&program);
+    let (outcome, accumulator) = execute(&instructions, Some(&mut profile));
+    print!("{}", profile.annotate());
+    println!("{:?} with acc={} after {} instructions", outcome, accumulator, profile.total());
+    for block in profile.hot_blocks().iter().take(5) {
+        println!("{:>5}..={:<5} {} hits, {} instructions", block.start, block.end, block.hits, block.executed());
+    }
 }
 
-fn part_1(instructions: &Vec<Instruction>) -> Result<i64, i64> {
-    execute(&instructions)
+/// Accumulator just before any instruction runs a second time
+fn part_1(instructions: &[Instruction]) -> Option<i64> {
+    match execute(instructions, None) {
+        (RunOutcome::Loop{..}, accumulator) => Some(accumulator),
+        _ => None,
+    }
 }
 
-fn part_2(instructions: &Vec<Instruction>) -> Result<i64, i64> {
-    for (i, instruction) in instructions.iter().enumerate() {
-        let replacement: Instruction;
-        match instruction.operation {
-            Operation::NOP => {
-                replacement = Instruction{
-                    operation: Operation::JMP, 
-                    argument: instruction.argument,
-                };
-            },
-            Operation::JMP => {
-                replacement = Instruction{
-                    operation: Operation::NOP, 
-                    argument: instruction.argument,
-                }
-            },
-            Operation::ACC => {
-                continue;
-            }
-        }
-        let mut tmp = instructions.to_vec();
-        tmp[i] = replacement;
-        let res = execute(&tmp);
-        if res.is_ok() {
-            return res;
-        }
+/// The one repair that makes the program terminate, and the accumulator after it
+fn part_2(instructions: &[Instruction]) -> Option<(Flip, i64)> {
+    let flip = ControlFlowGraph::new(instructions).repair()?;
+    match execute(&flip.apply(instructions), None) {
+        (RunOutcome::Terminated, accumulator) => Some((flip, accumulator)),
+        _ => None,
     }
-    Err(-1)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn parse_single_instruction() {
-        assert_eq!("nop +0".parse::<Instruction>().unwrap(), Instruction{
-            operation: Operation::NOP,
-            argument: 0
-        });
-        assert_eq!("jmp -3".parse::<Instruction>().unwrap(), Instruction{
-            operation: Operation::JMP,
-            argument: -3 
-        });
-        assert_eq!("acc +1".parse::<Instruction>().unwrap(), Instruction{
-            operation: Operation::ACC,
-            argument: 1 
-        });
-    }
-
     #[test]
     fn part_1_test() {
         let input = "nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\nacc +1\njmp -4\nacc +6";
         let input: Vec<Instruction> = input.lines()
             .filter_map(|line| line.parse().ok())
             .collect();
-        assert_eq!(part_1(&input).unwrap_err(), 5);
+        assert_eq!(part_1(&input), Some(5));
+        assert_eq!(part_1(&["acc +1".parse().unwrap()]), None);
     }
     #[test]
     fn part_2_test() {
@@ -143,6 +91,8 @@ mod tests {
         let input: Vec<Instruction> = input.lines()
             .filter_map(|line| line.parse().ok())
             .collect();
-        assert_eq!(part_2(&input).unwrap(), 8);
+        let (flip, accumulator) = part_2(&input).unwrap();
+        assert_eq!(accumulator, 8);
+        assert_eq!((flip.address, flip.replacement.to_string()), (7, String::from("nop -4")));
     }
 }